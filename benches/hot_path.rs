@@ -1,11 +1,16 @@
 use address_finder::{
-    address_matches, generate_private_key, private_key_to_address, private_key_to_address_bytes,
-    IncrementalKeygen, MatchRule,
+    address_from_words, address_matches, address_words_from_uncompressed,
+    addresses_from_uncompressed, generate_private_key, private_key_to_address,
+    private_key_to_address_bytes, seeded_private_key, uncompressed_to_address_bytes, BatchKeygen,
+    IncrementalKeygen, MatchRule, KECCAK_LANES, KEYGEN_BATCH_SIZE,
 };
 use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
 use rayon::prelude::*;
 use rayon::ThreadPoolBuilder;
 
+/// Key walks start from a fixed seed so every run measures the same keys.
+const SEED: u64 = 0;
+
 fn bench_keygen(c: &mut Criterion) {
     c.bench_function("keygen", |b| {
         b.iter(|| black_box(generate_private_key()));
@@ -70,6 +75,20 @@ fn bench_rule_reject(c: &mut Criterion) {
     });
 }
 
+fn bench_rule_reject_words(c: &mut Criterion) {
+    // The same rejection, straight from the Keccak state words.
+    let mut words = [[0u64; 3]];
+    address_words_from_uncompressed(
+        &BatchKeygen::from_secret(seeded_private_key(SEED, 0, 0)).next_batch()[..1],
+        &mut words,
+    );
+    let rule = MatchRule::new(&["0123456789"], &[] as &[&str]).unwrap();
+    let matcher = rule.word_matcher();
+    c.bench_function("rule_reject_words", |b| {
+        b.iter(|| black_box(matcher.may_match(black_box(&words[0]))));
+    });
+}
+
 fn bench_pipeline_single_bytes(c: &mut Criterion) {
     let rule = MatchRule::new(&["0123456789"], &[] as &[&str]).unwrap();
     c.bench_function("pipeline_single_bytes", |b| {
@@ -84,7 +103,7 @@ fn bench_pipeline_single_bytes(c: &mut Criterion) {
 fn bench_pipeline_incremental(c: &mut Criterion) {
     let rule = MatchRule::new(&["0123456789"], &[] as &[&str]).unwrap();
     c.bench_function("pipeline_incremental", |b| {
-        let mut kg = IncrementalKeygen::new();
+        let mut kg = IncrementalKeygen::from_secret(seeded_private_key(SEED, 0, 0));
         b.iter(|| {
             let addr = kg.address_bytes();
             let hit = rule.matches(&addr);
@@ -94,6 +113,69 @@ fn bench_pipeline_incremental(c: &mut Criterion) {
     });
 }
 
+fn bench_pipeline_batched(c: &mut Criterion) {
+    // Same work as `pipeline_incremental`, a whole BatchKeygen batch per
+    // iteration; throughput is per key.
+    let rule = MatchRule::new(&["0123456789"], &[] as &[&str]).unwrap();
+    let mut group = c.benchmark_group("pipeline_batched");
+    group.throughput(Throughput::Elements(KEYGEN_BATCH_SIZE as u64));
+    group.bench_function("batch", |b| {
+        let mut kg = BatchKeygen::from_secret(seeded_private_key(SEED, 0, 0));
+        b.iter(|| {
+            let mut hits = 0;
+            for public_key in kg.next_batch() {
+                let addr = uncompressed_to_address_bytes(public_key);
+                hits += rule.matches(&addr) as usize;
+            }
+            black_box(hits)
+        });
+    });
+    group.bench_function("lanes", |b| {
+        // As the finder runs it: the whole batch through the multi-lane hash,
+        // rejecting on the hash words before building addresses.
+        let mut kg = BatchKeygen::from_secret(seeded_private_key(SEED, 0, 0));
+        let mut hashes = vec![[0u64; 3]; KEYGEN_BATCH_SIZE];
+        let matcher = rule.word_matcher();
+        b.iter(|| {
+            address_words_from_uncompressed(kg.next_batch(), &mut hashes);
+            let hits = hashes
+                .iter()
+                .filter(|words| {
+                    matcher.may_match(words) && rule.matches(&address_from_words(words))
+                })
+                .count();
+            black_box(hits)
+        });
+    });
+    group.finish();
+}
+
+fn bench_address_hash(c: &mut Criterion) {
+    // Hash one keygen batch of public keys into addresses: one key at a time
+    // through tiny_keccak vs KECCAK_LANES at a time.
+    let mut kg = BatchKeygen::from_secret(seeded_private_key(SEED, 0, 0));
+    let keys = kg.next_batch().to_vec();
+    let mut out = vec![[0u8; 20]; keys.len()];
+
+    let mut group = c.benchmark_group("address_hash");
+    group.throughput(Throughput::Elements(keys.len() as u64));
+    group.bench_function("tiny_keccak", |b| {
+        b.iter(|| {
+            for (key, address) in keys.iter().zip(&mut out) {
+                *address = uncompressed_to_address_bytes(black_box(key));
+            }
+            black_box(&out);
+        });
+    });
+    group.bench_function(format!("lanes={KECCAK_LANES}"), |b| {
+        b.iter(|| {
+            addresses_from_uncompressed(black_box(&keys), &mut out);
+            black_box(&out);
+        });
+    });
+    group.finish();
+}
+
 fn bench_pipeline_multi(c: &mut Criterion) {
     // Fixed batch so criterion reports throughput as addresses-per-second.
     const BATCH: u64 = 4096;
@@ -139,8 +221,9 @@ fn bench_pipeline_multi_incremental(c: &mut Criterion) {
                 pool.install(|| {
                     // One seed scalar mult per thread, then BATCH/threads
                     // incremental steps on each thread.
-                    (0..threads as u64).into_par_iter().for_each(|_| {
-                        let mut kg = IncrementalKeygen::new();
+                    (0..threads as u64).into_par_iter().for_each(|worker| {
+                        let mut kg =
+                            IncrementalKeygen::from_secret(seeded_private_key(SEED, worker, 0));
                         let per_thread = BATCH / threads as u64;
                         for _ in 0..per_thread {
                             let addr = kg.address_bytes();
@@ -163,9 +246,12 @@ criterion_group!(
     bench_match_reject,
     bench_match_accept,
     bench_rule_reject,
+    bench_rule_reject_words,
     bench_pipeline_single,
     bench_pipeline_single_bytes,
     bench_pipeline_incremental,
+    bench_pipeline_batched,
+    bench_address_hash,
     bench_pipeline_multi,
     bench_pipeline_multi_incremental,
 );
@@ -1,9 +1,21 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
+
+use crate::crypto::{MatchRule, Pattern};
+use crate::dictionary::{Dictionary, WordPosition};
+use crate::keystore::{Kdf, KdfKind, KeystoreOptions};
+use crate::models::Password;
+use crate::say;
+use crate::stats::{format_count, Difficulty, CONFIDENCE_LEVELS};
+use crate::utils::{OutputFormat, STDOUT};
 use rpassword;
+use secp256k1::{PublicKey, SecretKey};
+use std::fs;
 use std::io;
+use zeroize::Zeroizing;
 
 /// Validate a single hex segment for use as an address prefix/suffix. Accepts
-/// an optional `0x` prefix and normalizes to lowercase.
+/// an optional `0x` prefix. Case is preserved so `--case-sensitive` can match
+/// against the EIP-55 checksum; see [`normalize_case`] for the default path.
 fn parse_hex_segment(s: &str) -> Result<String, String> {
     let trimmed = s.strip_prefix("0x").unwrap_or(s);
     if trimmed.is_empty() {
@@ -20,7 +32,7 @@ fn parse_hex_segment(s: &str) -> Result<String, String> {
             return Err(format!("non-hex character {c:?} at position {idx}"));
         }
     }
-    Ok(trimmed.to_ascii_lowercase())
+    Ok(trimmed.to_string())
 }
 
 /// Validate a comma-separated list of hex patterns (e.g. `001,002,003`).
@@ -38,6 +50,18 @@ fn parse_hex_patterns(s: &str) -> Result<String, String> {
     Ok(out.join(","))
 }
 
+/// Validate a comma-separated list of address patterns (see
+/// [`Pattern`](crate::crypto::Pattern) for the syntax). Case is preserved.
+fn parse_address_patterns(s: &str) -> Result<String, String> {
+    for seg in s.split(',') {
+        if seg.is_empty() {
+            return Err("empty alternative in comma-separated pattern".to_string());
+        }
+        Pattern::parse(seg, false)?;
+    }
+    Ok(s.to_string())
+}
+
 /// Expand a normalized comma-joined pattern string (as stored in `Args`) into
 /// a `Vec<String>` suitable for `MatchRule`, `create_rule`, etc.
 pub fn expand_arg(s: &Option<String>) -> Option<Vec<String>> {
@@ -45,6 +69,97 @@ pub fn expand_arg(s: &Option<String>) -> Option<Vec<String>> {
         .map(|raw| raw.split(',').map(String::from).collect())
 }
 
+/// Read prefix/suffix alternatives from a file, one per line. Blank lines
+/// and lines starting with `#` are skipped; every other line must be a
+/// valid hex segment (optional `0x`).
+pub fn read_alternatives_file(path: &str) -> Result<Vec<String>, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {path}: {e}"))?;
+    let mut alts = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        alts.push(parse_hex_segment(line).map_err(|e| format!("{path}:{}: {e}", number + 1))?);
+    }
+    if alts.is_empty() {
+        return Err(format!("{path} contains no alternatives"));
+    }
+    Ok(alts)
+}
+
+/// Alternatives from `--prefix`/`--suffix` followed by those in the
+/// matching `--prefix-file`/`--suffix-file`, if any.
+pub fn collect_alternatives(
+    inline: &Option<String>,
+    file: &Option<String>,
+) -> Result<Option<Vec<String>>, String> {
+    let mut alts = expand_arg(inline);
+    if let Some(path) = file {
+        alts.get_or_insert_with(Vec::new)
+            .extend(read_alternatives_file(path)?);
+    }
+    Ok(alts)
+}
+
+/// Parse exactly `N` bytes of hex (optional `0x`), e.g. a deployer address
+/// or a 32-byte init-code hash.
+fn parse_fixed_hex<const N: usize>(s: &str) -> Result<[u8; N], String> {
+    let trimmed = s.strip_prefix("0x").unwrap_or(s);
+    if trimmed.len() != 2 * N {
+        return Err(format!(
+            "expected {} hex characters, got {}",
+            2 * N,
+            trimmed.len()
+        ));
+    }
+    let mut out = [0u8; N];
+    hex::decode_to_slice(trimmed, &mut out).map_err(|e| format!("invalid hex: {e}"))?;
+    Ok(out)
+}
+
+/// Parse a secp256k1 public key as hex (optional `0x`): compressed (33
+/// bytes), uncompressed (65 bytes, `04` tag) or the bare 64-byte `X || Y`.
+pub fn parse_public_key(s: &str) -> Result<PublicKey, String> {
+    let trimmed = s.trim().strip_prefix("0x").unwrap_or(s.trim());
+    let mut bytes = hex::decode(trimmed).map_err(|e| format!("invalid hex: {e}"))?;
+    if bytes.len() == 64 {
+        bytes.insert(0, 0x04);
+    }
+    PublicKey::from_slice(&bytes).map_err(|e| format!("invalid public key: {e}"))
+}
+
+/// Parse a private key as 32 bytes of hex (optional `0x`).
+pub fn parse_secret_key(s: &str) -> Result<SecretKey, String> {
+    let bytes = parse_fixed_hex::<32>(s.trim())?;
+    SecretKey::from_slice(&bytes).map_err(|e| format!("invalid private key: {e}"))
+}
+
+/// Parse a BIP-39 mnemonic length.
+fn parse_mnemonic_word_count(s: &str) -> Result<usize, String> {
+    match s.parse() {
+        Ok(words @ (12 | 15 | 18 | 21 | 24)) => Ok(words),
+        _ => Err(format!("expected 12, 15, 18, 21 or 24 words, got {s:?}")),
+    }
+}
+
+/// Validate arbitrary-length hex bytes (optional `0x`), e.g. contract init
+/// code, returning the hex without the `0x` prefix.
+fn parse_hex_bytes(s: &str) -> Result<String, String> {
+    let trimmed = s.strip_prefix("0x").unwrap_or(s);
+    hex::decode(trimmed).map_err(|e| format!("invalid hex: {e}"))?;
+    Ok(trimmed.to_string())
+}
+
+/// Lowercase every alternative unless the search is case-sensitive, so
+/// filenames and log output stay canonical for plain nibble searches.
+pub fn normalize_case(alts: Option<Vec<String>>, case_sensitive: bool) -> Option<Vec<String>> {
+    if case_sensitive {
+        return alts;
+    }
+    alts.map(|alts| alts.iter().map(|a| a.to_ascii_lowercase()).collect())
+}
+
 /// Command-line arguments for the application
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -52,51 +167,378 @@ pub struct Args {
     /// Prefix for Ethereum address (without 0x). Use `,` to pass multiple
     /// alternatives, e.g. `--prefix ab,cd` matches addresses starting with
     /// either `ab` or `cd`.
-    #[arg(short, long, env = "ETH_PREFIX", value_parser = parse_hex_patterns)]
+    #[arg(short, long, env = "ETH_PREFIX", global = true, value_parser = parse_hex_patterns)]
     pub prefix: Option<String>,
 
     /// Suffix for Ethereum address (without 0x). Use `,` to pass multiple
     /// alternatives, e.g. `--suffix 001,002,003`.
-    #[arg(short, long, env = "ETH_SUFFIX", value_parser = parse_hex_patterns)]
+    #[arg(short, long, env = "ETH_SUFFIX", global = true, value_parser = parse_hex_patterns)]
     pub suffix: Option<String>,
 
+    /// File of prefix alternatives, one per line (`#` starts a comment
+    /// line). Added to any given with --prefix; tens of thousands are fine.
+    #[arg(long, value_name = "FILE", env = "ETH_PREFIX_FILE", global = true)]
+    pub prefix_file: Option<String>,
+
+    /// File of suffix alternatives, in the same format as --prefix-file
+    #[arg(long, value_name = "FILE", env = "ETH_SUFFIX_FILE", global = true)]
+    pub suffix_file: Option<String>,
+
+    /// Word list for hex-word vanity addresses, one word per line. Every hex
+    /// spelling of each word is searched (o→0, i/l→1, z→2, s→5, t→7,
+    /// g→6/9) and the word found is recorded with its key.
+    #[arg(
+        long,
+        value_name = "FILE",
+        env = "ETH_WORDS",
+        global = true,
+        conflicts_with_all = ["prefix", "suffix", "prefix_file", "suffix_file"]
+    )]
+    pub words: Option<String>,
+
+    /// Shortest word from --words to search for
+    #[arg(long, default_value_t = 4, env = "ETH_MIN_WORD_LENGTH", global = true)]
+    pub min_word_length: usize,
+
+    /// Where in the address a word from --words must appear
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = WordPosition::Start,
+        env = "ETH_WORD_POSITION",
+        global = true
+    )]
+    pub word_position: WordPosition,
+
+    /// Address pattern with `?` wildcards, `~N` runs of identical nibbles,
+    /// `{N}` repeats and `^`/`$` anchors, e.g. `^dead?beef`, `~6`, `c0ffee$`.
+    /// Use `,` to pass multiple alternatives. Combined with --prefix/--suffix,
+    /// all of them must match.
+    #[arg(long, env = "ETH_PATTERN", global = true, value_parser = parse_address_patterns)]
+    pub pattern: Option<String>,
+
     /// Number of matching addresses to find
-    #[arg(short, long, default_value = "1", env = "ETH_COUNT")]
+    #[arg(short, long, default_value = "1", env = "ETH_COUNT", global = true)]
     pub count: usize,
 
     /// Output directory for result files
-    #[arg(short, long, default_value = "results", env = "ETH_OUTPUT_DIR")]
+    #[arg(
+        short,
+        long,
+        default_value = "results",
+        env = "ETH_OUTPUT_DIR",
+        global = true
+    )]
     pub output_dir: String,
 
+    /// Write results to this file instead of a timestamped one in
+    /// --output-dir, or to stdout with `-` (messages then go to stderr)
+    #[arg(long, value_name = "FILE", env = "ETH_OUTPUT", global = true)]
+    pub output: Option<String>,
+
+    /// Results format. json and foundry rewrite one document after every
+    /// match; ndjson, csv and raw-keys append a line as each key is found.
+    /// Defaults to ndjson for `--output -`, to the --output file's
+    /// extension (.ndjson/.jsonl, .csv) and to json otherwise.
+    #[arg(long, value_enum, env = "ETH_FORMAT", global = true)]
+    pub format: Option<OutputFormat>,
+
+    /// Encrypt the results document to a passphrase, prompted for at the
+    /// start, before it touches disk. Read it back with `decrypt-results`.
+    #[arg(long, env = "ETH_ENCRYPT_RESULTS", global = true)]
+    pub encrypt_results: bool,
+
+    /// Encrypt the results document to age X25519 public keys (`age1…`)
+    /// instead of a passphrase; repeat or comma-separate for several.
+    /// Read it back with `decrypt-results --identity`.
+    #[arg(
+        long,
+        value_name = "RECIPIENT",
+        env = "ETH_ENCRYPT_TO",
+        value_delimiter = ',',
+        conflicts_with = "encrypt_results",
+        global = true
+    )]
+    pub encrypt_to: Vec<String>,
+
+    /// age identity file (`AGE-SECRET-KEY-1…`, as age-keygen writes) to
+    /// decrypt results encrypted with --encrypt-to, for `decrypt-results`
+    /// and --resume
+    #[arg(
+        short = 'i',
+        long,
+        value_name = "FILE",
+        env = "ETH_IDENTITY",
+        global = true
+    )]
+    pub identity: Option<String>,
+
     /// Generate keystore files instead of plain JSON
-    #[arg(short = 'e', long, env = "ETH_KEYSTORE")]
+    #[arg(short = 'e', long, env = "ETH_KEYSTORE", global = true)]
     pub keystore: bool,
 
     /// Directory for keystore files (defaults to output_dir/keystore if not specified)
-    #[arg(short = 'k', long, env = "ETH_KEYSTORE_DIR")]
+    #[arg(short = 'k', long, env = "ETH_KEYSTORE_DIR", global = true)]
     pub keystore_dir: Option<String>,
 
+    /// Key derivation function for keystore files: scrypt, or pbkdf2 for
+    /// wallets and hardware that need it
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = KdfKind::Scrypt,
+        env = "ETH_KEYSTORE_KDF",
+        global = true
+    )]
+    pub kdf: KdfKind,
+
+    /// scrypt cost N for keystore files, a power of two from 1024 to 2^20
+    #[arg(long, default_value_t = 8192, env = "ETH_SCRYPT_N", global = true)]
+    pub scrypt_n: u32,
+
+    /// scrypt block size r for keystore files
+    #[arg(long, default_value_t = 8, env = "ETH_SCRYPT_R", global = true)]
+    pub scrypt_r: u32,
+
+    /// scrypt parallelism p for keystore files
+    #[arg(long, default_value_t = 1, env = "ETH_SCRYPT_P", global = true)]
+    pub scrypt_p: u32,
+
+    /// PBKDF2-HMAC-SHA256 iterations for `--kdf pbkdf2`, at least 10000
+    #[arg(
+        long,
+        default_value_t = 262_144,
+        env = "ETH_PBKDF2_ITERATIONS",
+        global = true
+    )]
+    pub pbkdf2_iterations: u32,
+
+    /// Random salt per keystore file, in bytes (16 to 64)
+    #[arg(
+        long,
+        default_value_t = 32,
+        env = "ETH_KEYSTORE_SALT_LEN",
+        global = true
+    )]
+    pub keystore_salt_len: usize,
+
     /// Number of CPU cores to use for parallel processing (defaults to 1)
-    #[arg(short = 't', long, default_value_t = 1, env = "ETH_THREADS")]
+    #[arg(
+        short = 't',
+        long,
+        default_value_t = 1,
+        env = "ETH_THREADS",
+        global = true
+    )]
     pub threads: usize,
+
+    /// Match letters in prefix/suffix against the EIP-55 checksummed address,
+    /// e.g. `--prefix DeAd` only accepts addresses shown as `0xDeAd...`.
+    /// Results are written in checksummed form.
+    #[arg(short = 'C', long, env = "ETH_CASE_SENSITIVE", global = true)]
+    pub case_sensitive: bool,
+
+    /// Match the address of the contract each candidate key would deploy
+    /// with `CREATE`, instead of the key's own address
+    #[arg(long, env = "ETH_CONTRACT", global = true)]
+    pub contract: bool,
+
+    /// Deployer account nonce used to derive the contract address in
+    /// `--contract` mode (0 for the first deployment from a fresh account)
+    #[arg(
+        long,
+        default_value_t = 0,
+        env = "ETH_NONCE",
+        requires = "contract",
+        global = true
+    )]
+    pub nonce: u64,
+
+    /// INSECURE, for reproducible tests only: derive every starting key from
+    /// this seed instead of the OS random number generator. Anyone who knows
+    /// or guesses the seed can recompute the keys, so never fund them. Not
+    /// read from the environment, so it can't be left on by accident.
+    #[arg(long, value_name = "SEED", global = true)]
+    pub seed: Option<u64>,
+
+    /// Continue an interrupted search from its checkpoint file
+    /// (`*.checkpoint.json` next to the results file). The search criteria,
+    /// count and output files are taken from the checkpoint.
+    #[arg(long, value_name = "FILE")]
+    pub resume: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Search modes other than the default private-key search
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Mine CREATE2 salts for a fixed deployer and init code, matching the
+    /// resulting contract address against --prefix/--suffix
+    Create2(Create2Args),
+    /// Search for "gas-efficient" addresses with as many zero bytes as
+    /// possible, saving every improvement as it is found
+    Zeros(ZerosArgs),
+    /// Measure search throughput at increasing thread counts to help pick
+    /// --threads for a machine
+    Bench(BenchArgs),
+    /// Search on behalf of someone else's public key without learning the
+    /// final private key; only partial keys are written, for `combine`
+    Split(SplitArgs),
+    /// Add your private key to the partial keys found by `split` and verify
+    /// the resulting addresses
+    Combine(CombineArgs),
+    /// Search keys derived from fresh BIP-39 mnemonics, so the vanity
+    /// address can be recovered from its seed phrase
+    Mnemonic(MnemonicArgs),
+    /// Check or recover keystore files written with --keystore
+    #[command(subcommand)]
+    Keystore(KeystoreCommand),
+    /// Print the plaintext of a results file written with --encrypt-results,
+    /// or with --encrypt-to given the matching --identity
+    DecryptResults {
+        /// Encrypted results file
+        #[arg(value_name = "FILE")]
+        file: String,
+    },
+}
+
+/// Subcommands of `keystore`. Both prompt once for the keystore password.
+#[derive(Subcommand, Debug)]
+pub enum KeystoreCommand {
+    /// Decrypt keystore files and check that each key matches the address
+    /// in the filename and in the `address` field
+    Verify {
+        /// Keystore file, or a directory of them
+        #[arg(value_name = "PATH")]
+        path: String,
+    },
+    /// Print the private key of a keystore file, after typed confirmation
+    Export {
+        /// Keystore file
+        #[arg(value_name = "FILE")]
+        file: String,
+    },
+}
+
+/// Arguments for the `mnemonic` subcommand. The rule searched is
+/// --prefix/--suffix/--pattern/--words, as for the default search.
+#[derive(clap::Args, Debug)]
+pub struct MnemonicArgs {
+    /// Words per generated mnemonic: 12, 15, 18, 21 or 24
+    #[arg(
+        long,
+        default_value_t = 12,
+        env = "ETH_MNEMONIC_WORDS",
+        value_parser = parse_mnemonic_word_count
+    )]
+    pub mnemonic_words: usize,
+
+    /// Accounts m/44'/60'/0'/0/i checked per mnemonic, for i below this.
+    /// The default of 1 only checks index 0, so every match is the first
+    /// account a wallet shows after importing the mnemonic.
+    #[arg(
+        long,
+        default_value_t = 1,
+        env = "ETH_MNEMONIC_INDEXES",
+        value_parser = clap::value_parser!(u32).range(1..=1 << 31)
+    )]
+    pub indexes: u32,
+}
+
+/// Arguments for the `split` subcommand. The rule searched is
+/// --prefix/--suffix/--pattern/--words, as for the default search.
+#[derive(clap::Args, Debug)]
+pub struct SplitArgs {
+    /// Public key of the requester, who keeps its private key. Hex,
+    /// compressed or uncompressed.
+    #[arg(long, env = "ETH_SPLIT_PUBLIC_KEY", value_parser = parse_public_key)]
+    pub public_key: PublicKey,
+}
+
+/// Arguments for the `combine` subcommand
+#[derive(clap::Args, Debug)]
+pub struct CombineArgs {
+    /// Results file written by `split`
+    #[arg(value_name = "FILE")]
+    pub results: String,
+
+    /// File holding the private key of the split's public key as hex;
+    /// prompted for (without echo) if omitted
+    #[arg(long, value_name = "FILE")]
+    pub secret_key_file: Option<String>,
+}
+
+/// Arguments for the `bench` subcommand. The rule measured is
+/// --prefix/--suffix/--pattern if given, otherwise one that never matches.
+#[derive(clap::Args, Debug)]
+pub struct BenchArgs {
+    /// Seconds to run at each thread count
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u64).range(1..))]
+    pub duration: u64,
+
+    /// Highest thread count to measure (0 = all CPU cores); counts double
+    /// from 1 up to this value
+    #[arg(long, default_value_t = 0)]
+    pub max_threads: usize,
+
+    /// Print the results as JSON instead of a table
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for the `zeros` subcommand
+#[derive(clap::Args, Debug)]
+pub struct ZerosArgs {
+    /// Stop once an address with this many zero bytes is found
+    #[arg(long, env = "ETH_ZEROS_TARGET", value_parser = clap::value_parser!(u32).range(1..=20))]
+    pub target: Option<u32>,
+
+    /// Stop after this many seconds, keeping the best address so far
+    #[arg(long, env = "ETH_ZEROS_TIME_BUDGET")]
+    pub time_budget: Option<u64>,
+
+    /// Count zero bytes anywhere in the address instead of only leading ones
+    #[arg(long, env = "ETH_ZEROS_TOTAL")]
+    pub total: bool,
+}
+
+/// Arguments for the `create2` subcommand
+#[derive(clap::Args, Debug)]
+#[command(group(
+    clap::ArgGroup::new("init").required(true).args(["init_code_hash", "init_code"])
+))]
+pub struct Create2Args {
+    /// Address of the deploying contract (the factory calling CREATE2)
+    #[arg(long, env = "ETH_CREATE2_DEPLOYER", value_parser = parse_fixed_hex::<20>)]
+    pub deployer: [u8; 20],
+
+    /// Keccak-256 hash of the contract init code
+    #[arg(long, env = "ETH_CREATE2_INIT_CODE_HASH", value_parser = parse_fixed_hex::<32>)]
+    pub init_code_hash: Option<[u8; 32]>,
+
+    /// Contract init code as hex; hashed once before mining starts
+    #[arg(long, value_parser = parse_hex_bytes)]
+    pub init_code: Option<String>,
 }
 
 /// Get password from user with confirmation
-pub fn get_password() -> io::Result<String> {
-    println!("Enter password for keystore encryption:");
-    let password = rpassword::read_password()?;
+pub fn get_password(purpose: &str) -> io::Result<Password> {
+    say!("Enter password for {purpose}:");
+    let password = Password::from(rpassword::read_password()?);
 
-    if password.is_empty() {
+    if password.expose().is_empty() {
         return Err(io::Error::new(
             io::ErrorKind::InvalidInput,
             "Password cannot be empty",
         ));
     }
 
-    println!("Confirm password:");
-    let confirm_password = rpassword::read_password()?;
+    say!("Confirm password:");
+    let confirm_password = Password::from(rpassword::read_password()?);
 
-    if password != confirm_password {
+    if password.expose() != confirm_password.expose() {
         return Err(io::Error::new(
             io::ErrorKind::InvalidInput,
             "Passwords do not match",
@@ -106,41 +548,217 @@ pub fn get_password() -> io::Result<String> {
     Ok(password)
 }
 
-/// Create a rule string for the filename based on prefix and suffix. Multiple
-/// alternatives are joined with `-` rather than `,`, since commas aren't
-/// filesystem-safe on Windows.
-pub fn create_rule(prefix: &Option<Vec<String>>, suffix: &Option<Vec<String>>) -> String {
-    let prefix_str = prefix.as_deref().map(|alts| alts.join("-"));
-    let suffix_str = suffix.as_deref().map(|alts| alts.join("-"));
-    match (prefix_str, suffix_str) {
-        (Some(p), Some(s)) => format!("prefix_{p}_suffix_{s}"),
-        (Some(p), None) => format!("prefix_{p}"),
-        (None, Some(s)) => format!("suffix_{s}"),
-        (None, None) => "no_rule".to_string(),
+/// Prompt once, without echo, for the password of existing keystore or
+/// results files.
+pub fn read_password(what: &str) -> io::Result<Password> {
+    say!("Enter {what} password:");
+    rpassword::read_password().map(Password::from)
+}
+
+/// Ask the user to type `expected` to go ahead with something irreversible.
+pub fn confirm(prompt: &str, expected: &str) -> io::Result<bool> {
+    say!("{prompt}");
+    say!("Type {expected:?} to continue:");
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(answer.trim() == expected)
+}
+
+/// Read the private key for `combine` from `path`, or prompt for it without
+/// echo.
+pub fn read_secret_key(path: Option<&str>) -> Result<SecretKey, String> {
+    let hex = Zeroizing::new(match path {
+        Some(path) => {
+            fs::read_to_string(path).map_err(|e| format!("Failed to read {path}: {e}"))?
+        }
+        None => {
+            say!("Enter the private key of the split's public key:");
+            rpassword::read_password().map_err(|e| format!("Error reading private key: {e}"))?
+        }
+    });
+    parse_secret_key(&hex)
+}
+
+/// The results format from the command line: --format, or ndjson for
+/// `--output -`, or the one the --output file's extension names, otherwise
+/// json.
+pub fn output_format(args: &Args) -> OutputFormat {
+    if let Some(format) = args.format {
+        return format;
+    }
+    match args.output.as_deref() {
+        Some(STDOUT) => OutputFormat::Ndjson,
+        Some(path) if path.ends_with(".ndjson") || path.ends_with(".jsonl") => OutputFormat::Ndjson,
+        Some(path) if path.ends_with(".csv") => OutputFormat::Csv,
+        _ => OutputFormat::Json,
+    }
+}
+
+/// The validated keystore encryption settings from the command line.
+pub fn keystore_options(args: &Args) -> Result<KeystoreOptions, String> {
+    let kdf = match args.kdf {
+        KdfKind::Scrypt => Kdf::Scrypt {
+            n: args.scrypt_n,
+            r: args.scrypt_r,
+            p: args.scrypt_p,
+        },
+        KdfKind::Pbkdf2 => Kdf::Pbkdf2 {
+            iterations: args.pbkdf2_iterations,
+        },
+    };
+    let options = KeystoreOptions {
+        kdf,
+        salt_len: args.keystore_salt_len,
+    };
+    options.validate()?;
+    Ok(options)
+}
+
+/// Compile the expanded prefix/suffix/pattern alternatives into a
+/// [`MatchRule`].
+pub fn build_rule(
+    prefix: &Option<Vec<String>>,
+    suffix: &Option<Vec<String>>,
+    pattern: &Option<Vec<String>>,
+    case_sensitive: bool,
+) -> Result<MatchRule, String> {
+    let prefixes: &[String] = prefix.as_deref().unwrap_or(&[]);
+    let suffixes: &[String] = suffix.as_deref().unwrap_or(&[]);
+    let patterns: &[String] = pattern.as_deref().unwrap_or(&[]);
+    let rule = if case_sensitive {
+        MatchRule::new_case_sensitive(prefixes, suffixes)?
+    } else {
+        MatchRule::new(prefixes, suffixes)?
+    };
+    rule.with_patterns(patterns)
+}
+
+/// Spell out the pattern metacharacters, none of which are safe in
+/// filenames everywhere: `^dead?beef` becomes `start.deadxbeef`.
+fn encode_pattern(pattern: &str) -> String {
+    let mut out = String::with_capacity(pattern.len() + 8);
+    for c in pattern.chars() {
+        match c {
+            '^' => out.push_str("start."),
+            '$' => out.push_str(".end"),
+            '?' => out.push('x'),
+            '~' => out.push_str("run"),
+            '{' => out.push('r'),
+            '}' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Longest run of alternatives spelled out in a filename or the search
+/// banner; longer lists (e.g. from --prefix-file) are summarized.
+const MAX_LISTED_ALTS: usize = 8;
+
+/// `alts` joined with `separator`, or the first few and a count of the rest.
+fn list_alternatives(alts: &[String], separator: &str, more: impl Fn(usize) -> String) -> String {
+    if alts.len() <= MAX_LISTED_ALTS {
+        return alts.join(separator);
+    }
+    let shown = alts[..MAX_LISTED_ALTS].join(separator);
+    format!("{shown}{separator}{}", more(alts.len() - MAX_LISTED_ALTS))
+}
+
+/// Create a rule string for the filename based on prefix, suffix and
+/// pattern. Multiple alternatives are joined with `-` rather than `,`, since
+/// commas aren't filesystem-safe on Windows.
+pub fn create_rule(
+    prefix: &Option<Vec<String>>,
+    suffix: &Option<Vec<String>>,
+    pattern: &Option<Vec<String>>,
+) -> String {
+    let more = |n| format!("{n}more");
+    let mut parts = Vec::new();
+    if let Some(alts) = prefix {
+        parts.push(format!("prefix_{}", list_alternatives(alts, "-", more)));
+    }
+    if let Some(alts) = suffix {
+        parts.push(format!("suffix_{}", list_alternatives(alts, "-", more)));
+    }
+    if let Some(alts) = pattern {
+        let encoded: Vec<String> = alts.iter().map(|p| encode_pattern(p)).collect();
+        parts.push(format!("pattern_{}", encoded.join("-")));
+    }
+    if parts.is_empty() {
+        "no_rule".to_string()
+    } else {
+        parts.join("_")
     }
 }
 
 /// Print information about the search criteria
-pub fn print_search_info(prefix: &Option<Vec<String>>, suffix: &Option<Vec<String>>, count: usize) {
-    println!("Ethereum Address Finder");
-    println!("Looking for addresses with:");
+pub fn print_search_info(
+    prefix: &Option<Vec<String>>,
+    suffix: &Option<Vec<String>>,
+    pattern: &Option<Vec<String>>,
+    dictionary: Option<&Dictionary>,
+    count: usize,
+) {
+    say!("Ethereum Address Finder");
+    say!("Looking for addresses with:");
+    let more = |n| format!("... and {n} more");
     if let Some(prefix) = prefix {
-        println!("  Prefix: {}", prefix.join(", "));
+        say!("  Prefix: {}", list_alternatives(prefix, ", ", more));
     }
     if let Some(suffix) = suffix {
-        println!("  Suffix: {}", suffix.join(", "));
+        say!("  Suffix: {}", list_alternatives(suffix, ", ", more));
+    }
+    if let Some(pattern) = pattern {
+        say!("  Pattern: {}", pattern.join(", "));
     }
-    println!("Finding {count} matching addresses...");
+    if let Some(dictionary) = dictionary {
+        let position = match dictionary.position {
+            WordPosition::Start => "at the start",
+            WordPosition::End => "at the end",
+            WordPosition::Anywhere => "anywhere",
+        };
+        say!(
+            "  Words: {} words ({} hex spellings) {position}",
+            dictionary.word_count(),
+            dictionary.len()
+        );
+    }
+    say!("Finding {count} matching addresses...");
+}
+
+/// Print how many candidates `count` matches should take on average and at
+/// each of [`CONFIDENCE_LEVELS`].
+pub fn print_difficulty(difficulty: &Difficulty, count: usize) {
+    say!(
+        "Difficulty: 1 in {} candidates match",
+        format_count(1.0 / difficulty.probability())
+    );
+    let quantiles: Vec<String> = CONFIDENCE_LEVELS
+        .iter()
+        .map(|&q| {
+            format!(
+                "{:.0}%: {}",
+                q * 100.0,
+                format_count(difficulty.attempts_for(count, q))
+            )
+        })
+        .collect();
+    say!(
+        "Expected work: {} candidates ({})",
+        format_count(difficulty.expected_attempts(count)),
+        quantiles.join(", ")
+    );
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::test_dir;
 
     #[test]
     fn parse_hex_segment_accepts_valid() {
         assert_eq!(parse_hex_segment("abc").unwrap(), "abc");
-        assert_eq!(parse_hex_segment("0xABC").unwrap(), "abc");
+        assert_eq!(parse_hex_segment("0xABC").unwrap(), "ABC");
         assert_eq!(
             parse_hex_segment("0123456789abcdef").unwrap(),
             "0123456789abcdef"
@@ -182,8 +800,22 @@ mod tests {
     #[test]
     fn args_accepts_single_suffix() {
         let args = Args::try_parse_from(["prog", "--suffix", "0xDEAD"]).unwrap();
-        assert_eq!(args.suffix, Some("dead".to_string()));
-        assert_eq!(expand_arg(&args.suffix), Some(vec!["dead".to_string()]));
+        assert_eq!(args.suffix, Some("DEAD".to_string()));
+        assert_eq!(
+            normalize_case(expand_arg(&args.suffix), args.case_sensitive),
+            Some(vec!["dead".to_string()])
+        );
+    }
+
+    #[test]
+    fn args_case_sensitive_preserves_case() {
+        let args =
+            Args::try_parse_from(["prog", "--prefix", "DeAd,bEEf", "--case-sensitive"]).unwrap();
+        assert!(args.case_sensitive);
+        assert_eq!(
+            normalize_case(expand_arg(&args.prefix), args.case_sensitive),
+            Some(vec!["DeAd".to_string(), "bEEf".to_string()])
+        );
     }
 
     #[test]
@@ -193,6 +825,24 @@ mod tests {
         assert!(Args::try_parse_from(["prog", "--suffix", "001,"]).is_err());
     }
 
+    #[test]
+    fn output_format_follows_the_destination_unless_given() {
+        let format = |extra: &[&str]| {
+            let args =
+                Args::try_parse_from(["prog", "--prefix", "ab"].iter().chain(extra)).unwrap();
+            output_format(&args)
+        };
+        assert_eq!(format(&[]), OutputFormat::Json);
+        assert_eq!(format(&["--output", "-"]), OutputFormat::Ndjson);
+        assert_eq!(format(&["--output", "keys.jsonl"]), OutputFormat::Ndjson);
+        assert_eq!(format(&["--output", "keys.json"]), OutputFormat::Json);
+        assert_eq!(format(&["--output", "keys.csv"]), OutputFormat::Csv);
+        assert_eq!(
+            format(&["--output", "keys.txt", "--format", "raw-keys"]),
+            OutputFormat::RawKeys
+        );
+    }
+
     #[test]
     fn args_rejects_non_hex_segment() {
         assert!(Args::try_parse_from(["prog", "--suffix", "001,xyz"]).is_err());
@@ -202,9 +852,204 @@ mod tests {
     fn create_rule_joins_alternatives_with_dash() {
         let p = Some(vec!["ab".to_string(), "cd".to_string()]);
         let s = Some(vec!["01".to_string(), "02".to_string(), "03".to_string()]);
-        assert_eq!(create_rule(&p, &s), "prefix_ab-cd_suffix_01-02-03");
-        assert_eq!(create_rule(&None, &s), "suffix_01-02-03");
-        assert_eq!(create_rule(&p, &None), "prefix_ab-cd");
-        assert_eq!(create_rule(&None, &None), "no_rule");
+        assert_eq!(create_rule(&p, &s, &None), "prefix_ab-cd_suffix_01-02-03");
+        assert_eq!(create_rule(&None, &s, &None), "suffix_01-02-03");
+        assert_eq!(create_rule(&p, &None, &None), "prefix_ab-cd");
+        assert_eq!(create_rule(&None, &None, &None), "no_rule");
+    }
+
+    #[test]
+    fn create_rule_encodes_patterns_filesystem_safe() {
+        let pat = Some(vec!["^dead?beef".to_string(), "~6".to_string()]);
+        assert_eq!(
+            create_rule(&None, &None, &pat),
+            "pattern_start.deadxbeef-run6"
+        );
+        let pat = Some(vec!["0{4}c0ffee$".to_string()]);
+        let p = Some(vec!["ab".to_string()]);
+        let rule = create_rule(&p, &None, &pat);
+        assert_eq!(rule, "prefix_ab_pattern_0r4c0ffee.end");
+        assert!(!rule.contains(['^', '$', '?', '~', '{', '}', ',']));
+    }
+
+    #[test]
+    fn create_rule_summarizes_long_alternative_lists() {
+        let many: Vec<String> = (0..1000).map(|i| format!("{i:04x}")).collect();
+        assert_eq!(
+            create_rule(&Some(many), &None, &None),
+            "prefix_0000-0001-0002-0003-0004-0005-0006-0007-992more"
+        );
+    }
+
+    #[test]
+    fn alternatives_files_merge_with_inline_alternatives() {
+        let dir = test_dir("alternatives_file");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("prefixes.txt");
+        let path = path.to_str().unwrap().to_string();
+
+        fs::write(&path, "# hex words\ndead\n\n  0xBEEF \nc0ffee\n").unwrap();
+        assert_eq!(
+            collect_alternatives(&Some("ab,cd".to_string()), &Some(path.clone())).unwrap(),
+            Some(
+                vec!["ab", "cd", "dead", "BEEF", "c0ffee"]
+                    .into_iter()
+                    .map(String::from)
+                    .collect()
+            )
+        );
+        assert_eq!(
+            collect_alternatives(&None, &Some(path.clone()))
+                .unwrap()
+                .unwrap()
+                .len(),
+            3
+        );
+        assert_eq!(collect_alternatives(&None, &None).unwrap(), None);
+
+        fs::write(&path, "dead\nbeer\n").unwrap();
+        let err = read_alternatives_file(&path).unwrap_err();
+        assert!(err.contains("prefixes.txt:2:"), "{err}");
+        fs::write(&path, "# nothing\n").unwrap();
+        assert!(read_alternatives_file(&path).is_err());
+        assert!(read_alternatives_file(dir.join("missing").to_str().unwrap()).is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn args_accepts_and_validates_patterns() {
+        let args = Args::try_parse_from(["prog", "--pattern", "^dead?beef,~6"]).unwrap();
+        assert_eq!(
+            expand_arg(&args.pattern),
+            Some(vec!["^dead?beef".to_string(), "~6".to_string()])
+        );
+        assert!(Args::try_parse_from(["prog", "--pattern", "^xyz"]).is_err());
+        assert!(Args::try_parse_from(["prog", "--pattern", "ab,"]).is_err());
+    }
+
+    #[test]
+    fn args_contract_mode_defaults_nonce_to_zero() {
+        let args = Args::try_parse_from(["prog", "--prefix", "00", "--contract"]).unwrap();
+        assert!(args.contract);
+        assert_eq!(args.nonce, 0);
+
+        let args =
+            Args::try_parse_from(["prog", "--prefix", "00", "--contract", "--nonce", "3"]).unwrap();
+        assert_eq!(args.nonce, 3);
+    }
+
+    #[test]
+    fn args_create2_subcommand_takes_shared_options() {
+        let deployer = "0x4e59b44847b379578588920cA78FbF26c0B4956C";
+        let hash = "ab".repeat(32);
+        let args = Args::try_parse_from([
+            "prog",
+            "create2",
+            "--deployer",
+            deployer,
+            "--init-code-hash",
+            &hash,
+            "--prefix",
+            "0000",
+            "--threads",
+            "4",
+        ])
+        .unwrap();
+        assert_eq!(args.prefix, Some("0000".to_string()));
+        assert_eq!(args.threads, 4);
+        match args.command {
+            Some(Command::Create2(c)) => {
+                assert_eq!(c.deployer[0], 0x4e);
+                assert_eq!(c.init_code_hash, Some([0xab; 32]));
+                assert!(c.init_code.is_none());
+            }
+            _ => panic!("expected create2 subcommand"),
+        }
+    }
+
+    #[test]
+    fn args_create2_requires_init_code_or_hash() {
+        let deployer = "4e59b44847b379578588920ca78fbf26c0b4956c";
+        assert!(Args::try_parse_from(["prog", "create2", "--deployer", deployer]).is_err());
+        assert!(Args::try_parse_from([
+            "prog",
+            "create2",
+            "--deployer",
+            deployer,
+            "--init-code",
+            "6000",
+            "--init-code-hash",
+            &"00".repeat(32),
+        ])
+        .is_err());
+        assert!(Args::try_parse_from([
+            "prog",
+            "create2",
+            "--deployer",
+            "abcd",
+            "--init-code",
+            "00"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn args_zeros_subcommand_accepts_stop_conditions() {
+        let args = Args::try_parse_from([
+            "prog",
+            "zeros",
+            "--target",
+            "4",
+            "--time-budget",
+            "3600",
+            "--contract",
+        ])
+        .unwrap();
+        assert!(args.contract);
+        match args.command {
+            Some(Command::Zeros(z)) => {
+                assert_eq!(z.target, Some(4));
+                assert_eq!(z.time_budget, Some(3600));
+                assert!(!z.total);
+            }
+            _ => panic!("expected zeros subcommand"),
+        }
+        assert!(Args::try_parse_from(["prog", "zeros", "--target", "21"]).is_err());
+    }
+
+    #[test]
+    fn args_bench_subcommand_defaults() {
+        let args = Args::try_parse_from(["prog", "bench"]).unwrap();
+        match args.command {
+            Some(Command::Bench(b)) => {
+                assert_eq!(b.duration, 3);
+                assert_eq!(b.max_threads, 0);
+                assert!(!b.json);
+            }
+            other => panic!("expected bench, got {other:?}"),
+        }
+        let args = Args::try_parse_from([
+            "prog",
+            "bench",
+            "--duration",
+            "10",
+            "--max-threads",
+            "8",
+            "--json",
+            "--prefix",
+            "dead",
+        ])
+        .unwrap();
+        assert_eq!(args.prefix.as_deref(), Some("dead"));
+        assert!(matches!(
+            args.command,
+            Some(Command::Bench(BenchArgs {
+                duration: 10,
+                max_threads: 8,
+                json: true
+            }))
+        ));
+        assert!(Args::try_parse_from(["prog", "bench", "--duration", "0"]).is_err());
     }
 }
@@ -0,0 +1,268 @@
+//! Batched public key generation for the search hot loop.
+//!
+//! Stepping `P_{i+1} = P_i + G` through `PublicKey::combine` costs a field
+//! inversion per key, because libsecp256k1 returns affine points. Here the
+//! walk stays in Jacobian coordinates `(X, Y, Z)` (affine `x = X/Z²`,
+//! `y = Y/Z³`), where adding the fixed affine point `G` needs no inversion,
+//! and a whole batch is converted back to affine with a single inversion
+//! using Montgomery's trick: invert the product of all `Z`s once, then peel
+//! off each individual inverse with two multiplications.
+
+use k256::FieldElement;
+use rand::rngs::OsRng;
+use secp256k1::{PublicKey, Scalar, SecretKey};
+
+use super::SECP;
+
+/// Keys produced per [`BatchKeygen::next_batch`] call.
+pub const KEYGEN_BATCH_SIZE: usize = 1024;
+
+/// A point in Jacobian coordinates. Coordinates are kept at magnitude 1
+/// (weakly normalized) between operations.
+#[derive(Clone, Copy)]
+struct Jacobian {
+    x: FieldElement,
+    y: FieldElement,
+    z: FieldElement,
+}
+
+impl Jacobian {
+    /// `self + (x2, y2)` for an affine point, using 8 multiplications and 3
+    /// squarings.
+    ///
+    /// The formula has no case for `self == ±(x2, y2)`; for a walk from a
+    /// random starting point that would mean having reached the key ±1.
+    #[inline]
+    fn add_affine(&self, x2: &FieldElement, y2: &FieldElement) -> Self {
+        let z1z1 = self.z.square();
+        let u2 = x2.mul(&z1z1);
+        let s2 = y2.mul(&self.z).mul(&z1z1);
+        let h = u2 + self.x.negate(1);
+        let r = s2 + self.y.negate(1);
+        let hh = h.square();
+        let hhh = h.mul(&hh);
+        let v = self.x.mul(&hh);
+        let x3 = (r.square() + hhh.negate(1) + v.double().negate(2)).normalize_weak();
+        let y3 = (r.mul(&(v + x3.negate(1))) + self.y.mul(&hhh).negate(1)).normalize_weak();
+        let z3 = self.z.mul(&h);
+        Self {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+}
+
+/// Generates consecutive keys `k, k+1, k+2, ...` a batch at a time, returning
+/// each batch's public keys as uncompressed `X || Y` (64 bytes, no `0x04`
+/// tag), ready for Keccak.
+///
+/// Costs roughly 18 field multiplications per key plus one inversion per
+/// [`KEYGEN_BATCH_SIZE`] keys, against an inversion per key for
+/// `PublicKey::combine`. Private keys are only materialized on request, via
+/// [`BatchKeygen::secret`].
+pub struct BatchKeygen {
+    /// Private key (or, for [`BatchKeygen::from_public_offset`], offset) of
+    /// the first entry of the current batch.
+    base: SecretKey,
+    /// Jacobian point for the first key of the next batch.
+    next: Jacobian,
+    g_x: FieldElement,
+    g_y: FieldElement,
+    points: Vec<Jacobian>,
+    /// Prefix products of the `Z`s, reused as scratch by the inversion.
+    products: Vec<FieldElement>,
+    public_keys: Vec<[u8; 64]>,
+    /// Whether `next_batch` has been called yet.
+    started: bool,
+}
+
+impl BatchKeygen {
+    /// Seed from a fresh random key, performing one scalar multiplication.
+    pub fn new() -> Self {
+        Self::from_secret(SecretKey::new(&mut OsRng))
+    }
+
+    /// Start the walk at `secret`.
+    pub fn from_secret(secret: SecretKey) -> Self {
+        let public = SECP.with(|secp| PublicKey::from_secret_key(secp, &secret));
+        Self::starting_at(secret, &public)
+    }
+
+    /// Walk the points `public + k·G` for `k = offset, offset+1, ...`, for
+    /// split-key searches where `public` belongs to someone else. The
+    /// "secrets" reported by [`BatchKeygen::secret`] are then the offsets
+    /// `k`, which only give the private key when added to `public`'s.
+    pub fn from_public_offset(public: &PublicKey, offset: SecretKey) -> Self {
+        let start = SECP.with(|secp| PublicKey::from_secret_key(secp, &offset));
+        // The sum is the point at infinity only if `offset` is the negated
+        // secret of `public`, which is as likely as guessing that secret.
+        let start = public
+            .combine(&start)
+            .expect("offset is not the negated secret of the public key");
+        Self::starting_at(offset, &start)
+    }
+
+    fn starting_at(base: SecretKey, public: &PublicKey) -> Self {
+        let g = SECP.with(|secp| PublicKey::from_secret_key(secp, &scalar_secret(1)));
+        let (x, y) = affine_coordinates(public);
+        let (g_x, g_y) = affine_coordinates(&g);
+        Self {
+            base,
+            next: Jacobian {
+                x,
+                y,
+                z: FieldElement::ONE,
+            },
+            g_x,
+            g_y,
+            points: Vec::with_capacity(KEYGEN_BATCH_SIZE),
+            products: Vec::with_capacity(KEYGEN_BATCH_SIZE),
+            public_keys: vec![[0u8; 64]; KEYGEN_BATCH_SIZE],
+            started: false,
+        }
+    }
+
+    /// Advance to the next [`KEYGEN_BATCH_SIZE`] keys and return their
+    /// public keys; entry `i` belongs to [`BatchKeygen::secret`]`(i)`.
+    pub fn next_batch(&mut self) -> &[[u8; 64]] {
+        if self.started {
+            self.base = add_scalar(&self.base, KEYGEN_BATCH_SIZE as u64);
+        }
+        self.started = true;
+
+        self.points.clear();
+        let mut point = self.next;
+        for _ in 0..KEYGEN_BATCH_SIZE {
+            self.points.push(point);
+            point = point.add_affine(&self.g_x, &self.g_y);
+        }
+        self.next = point;
+
+        // Montgomery batch inversion: products[i] = z_0 * ... * z_{i-1}.
+        self.products.clear();
+        let mut acc = FieldElement::ONE;
+        for p in &self.points {
+            self.products.push(acc);
+            acc = acc.mul(&p.z);
+        }
+        // Non-zero: every Z is a product of non-zero H values (see
+        // `Jacobian::add_affine`).
+        let mut inv = acc.invert().expect("product of Z coordinates is non-zero");
+        for i in (0..KEYGEN_BATCH_SIZE).rev() {
+            let p = &self.points[i];
+            let z_inv = inv.mul(&self.products[i]);
+            inv = inv.mul(&p.z);
+
+            let z_inv2 = z_inv.square();
+            let x = p.x.mul(&z_inv2);
+            let y = p.y.mul(&z_inv2.mul(&z_inv));
+            let out = &mut self.public_keys[i];
+            out[..32].copy_from_slice(&x.to_bytes());
+            out[32..].copy_from_slice(&y.to_bytes());
+        }
+        &self.public_keys
+    }
+
+    /// Public keys of the batch last returned by [`BatchKeygen::next_batch`].
+    pub fn public_keys(&self) -> &[[u8; 64]] {
+        &self.public_keys
+    }
+
+    /// Private key of entry `index` of the batch last returned by
+    /// [`BatchKeygen::next_batch`].
+    pub fn secret(&self, index: usize) -> SecretKey {
+        add_scalar(&self.base, index as u64)
+    }
+}
+
+impl Default for BatchKeygen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn scalar_secret(value: u64) -> SecretKey {
+    let mut bytes = [0u8; 32];
+    bytes[24..].copy_from_slice(&value.to_be_bytes());
+    SecretKey::from_slice(&bytes).expect("small non-zero scalar is a valid key")
+}
+
+/// `secret + value` mod n.
+fn add_scalar(secret: &SecretKey, value: u64) -> SecretKey {
+    if value == 0 {
+        return *secret;
+    }
+    // Overflow past the group order n ≈ 2²⁵⁶ is not reachable in practice.
+    secret
+        .add_tweak(&Scalar::from(scalar_secret(value)))
+        .expect("scalar order overflow is unreachable")
+}
+
+fn affine_coordinates(public: &PublicKey) -> (FieldElement, FieldElement) {
+    let serialized = public.serialize_uncompressed();
+    let coordinate = |bytes: &[u8]| {
+        FieldElement::from_bytes(bytes.into()).expect("public key coordinates are field elements")
+    };
+    (
+        coordinate(&serialized[1..33]),
+        coordinate(&serialized[33..]),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uncompressed(secret: &SecretKey) -> [u8; 64] {
+        let public = SECP.with(|secp| PublicKey::from_secret_key(secp, secret));
+        let mut out = [0u8; 64];
+        out.copy_from_slice(&public.serialize_uncompressed()[1..]);
+        out
+    }
+
+    #[test]
+    fn batches_match_scalar_multiplication_across_boundaries() {
+        let mut kg = BatchKeygen::new();
+        for _ in 0..3 {
+            let batch = kg.next_batch().to_vec();
+            assert_eq!(batch.len(), KEYGEN_BATCH_SIZE);
+            for i in [0, 1, 2, 511, KEYGEN_BATCH_SIZE - 2, KEYGEN_BATCH_SIZE - 1] {
+                assert_eq!(batch[i], uncompressed(&kg.secret(i)), "entry {i}");
+            }
+        }
+    }
+
+    #[test]
+    fn walk_is_consecutive_from_the_seed() {
+        let seed = scalar_secret(1000);
+        let mut kg = BatchKeygen::from_secret(seed);
+        kg.next_batch();
+        assert_eq!(kg.secret(0), seed);
+        assert_eq!(kg.secret(5), scalar_secret(1005));
+        let second = kg.next_batch()[3];
+        assert_eq!(
+            kg.secret(3),
+            scalar_secret(1000 + KEYGEN_BATCH_SIZE as u64 + 3)
+        );
+        assert_eq!(second, uncompressed(&kg.secret(3)));
+    }
+
+    #[test]
+    fn public_offset_walk_adds_the_offset_to_the_public_key() {
+        let user = scalar_secret(0xdead_beef);
+        let public = SECP.with(|secp| PublicKey::from_secret_key(secp, &user));
+        let mut kg = BatchKeygen::from_public_offset(&public, scalar_secret(7));
+        kg.next_batch();
+        let batch = kg.next_batch().to_vec();
+        for i in [0, 1, KEYGEN_BATCH_SIZE - 1] {
+            let offset = kg.secret(i);
+            assert_eq!(
+                offset,
+                scalar_secret(7 + KEYGEN_BATCH_SIZE as u64 + i as u64)
+            );
+            let combined = add_scalar(&user, 7 + KEYGEN_BATCH_SIZE as u64 + i as u64);
+            assert_eq!(batch[i], uncompressed(&combined), "entry {i}");
+        }
+    }
+}
@@ -0,0 +1,192 @@
+//! BIP-39 mnemonics and BIP-32 derivation, for vanity keys that can be
+//! recovered from a seed phrase.
+//!
+//! Only private (`xprv`-style) derivation is needed: every key searched is
+//! derived from a mnemonic generated on this machine. Each [`ExtendedKey`]
+//! carries its public key, because the search needs the child's address
+//! anyway and non-hardened derivation needs the parent's.
+
+use bip39::Mnemonic;
+use hmac::{Hmac, Mac};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use secp256k1::{PublicKey, Scalar, SecretKey};
+use sha2::Sha512;
+
+use super::{public_key_to_address_bytes, SECP};
+
+/// Parent of the standard Ethereum accounts `m/44'/60'/0'/0/i`.
+pub const ETH_ACCOUNT_PATH: &str = "m/44'/60'/0'/0";
+
+/// Child indices from this one up are hardened (`i'`).
+const HARDENED: u32 = 1 << 31;
+
+/// A BIP-32 extended private key.
+#[derive(Clone)]
+pub struct ExtendedKey {
+    secret: SecretKey,
+    public: PublicKey,
+    chain_code: [u8; 32],
+}
+
+impl ExtendedKey {
+    /// Master key `m` of a BIP-32 seed, e.g. a mnemonic's `to_seed("")`.
+    pub fn master(seed: &[u8]) -> Result<Self, String> {
+        let (key, chain_code) = hmac_sha512(b"Bitcoin seed", &[seed]);
+        // Out of range with probability ~2⁻¹²⁷.
+        let secret =
+            SecretKey::from_slice(&key).map_err(|_| "master key is out of range".to_string())?;
+        Ok(Self::new(secret, chain_code))
+    }
+
+    /// Child `index` (hardened if `index >= 2³¹`).
+    pub fn child(&self, index: u32) -> Result<Self, String> {
+        let (tweak, chain_code) = if index >= HARDENED {
+            let mut data = [0u8; 33];
+            data[1..].copy_from_slice(&self.secret.secret_bytes());
+            hmac_sha512(&self.chain_code, &[&data, &index.to_be_bytes()])
+        } else {
+            hmac_sha512(
+                &self.chain_code,
+                &[&self.public.serialize(), &index.to_be_bytes()],
+            )
+        };
+        // Out of range, or a zero child key, with probability ~2⁻¹²⁷;
+        // BIP-32 then skips the index.
+        let secret = Scalar::from_be_bytes(tweak)
+            .ok()
+            .and_then(|tweak| self.secret.add_tweak(&tweak).ok())
+            .ok_or_else(|| format!("child {index} is not a valid key"))?;
+        Ok(Self::new(secret, chain_code))
+    }
+
+    /// Descendant at `path`, e.g. `m/44'/60'/0'/0` (`h` also marks hardened
+    /// indices).
+    pub fn derive(&self, path: &str) -> Result<Self, String> {
+        let mut parts = path.split('/');
+        if parts.next() != Some("m") {
+            return Err(format!("derivation path must start with m/ (got {path:?})"));
+        }
+        let mut key = self.clone();
+        for part in parts {
+            let (digits, hardened) = match part.strip_suffix(['\'', 'h']) {
+                Some(digits) => (digits, true),
+                None => (part, false),
+            };
+            let index: u32 = digits
+                .parse()
+                .ok()
+                .filter(|&index| index < HARDENED)
+                .ok_or_else(|| format!("invalid index {part:?} in derivation path {path:?}"))?;
+            key = key.child(if hardened { index | HARDENED } else { index })?;
+        }
+        Ok(key)
+    }
+
+    pub fn secret(&self) -> SecretKey {
+        self.secret
+    }
+
+    /// Address of this key, without another scalar multiplication.
+    pub fn address_bytes(&self) -> [u8; 20] {
+        public_key_to_address_bytes(&self.public)
+    }
+
+    fn new(secret: SecretKey, chain_code: [u8; 32]) -> Self {
+        let public = SECP.with(|secp| PublicKey::from_secret_key(secp, &secret));
+        Self {
+            secret,
+            public,
+            chain_code,
+        }
+    }
+}
+
+/// `IL || IR = HMAC-SHA512(key, data)`, split into `IL` (a key or tweak)
+/// and the chain code `IR`.
+fn hmac_sha512(key: &[u8], data: &[&[u8]]) -> ([u8; 32], [u8; 32]) {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    for part in data {
+        mac.update(part);
+    }
+    let digest = mac.finalize().into_bytes();
+    let (left, right) = digest.split_at(32);
+    (
+        left.try_into().expect("32-byte half"),
+        right.try_into().expect("32-byte half"),
+    )
+}
+
+/// Check that `word_count` is a BIP-39 mnemonic length.
+pub fn check_mnemonic_word_count(word_count: usize) -> Result<(), String> {
+    if !(12..=24).contains(&word_count) || word_count % 3 != 0 {
+        return Err(format!(
+            "mnemonics have 12, 15, 18, 21 or 24 words (got {word_count})"
+        ));
+    }
+    Ok(())
+}
+
+/// A fresh English mnemonic of `word_count` words (12, 15, 18, 21 or 24).
+pub fn generate_mnemonic(word_count: usize) -> Result<Mnemonic, String> {
+    check_mnemonic_word_count(word_count)?;
+    let mut entropy = [0u8; 32];
+    let entropy = &mut entropy[..word_count / 3 * 4];
+    OsRng.fill_bytes(entropy);
+    Mnemonic::from_entropy(entropy).map_err(|e| e.to_string())
+}
+
+/// The key at [`ETH_ACCOUNT_PATH`] of `mnemonic` (no passphrase), whose
+/// children are the wallet's accounts.
+pub fn eth_account_parent(mnemonic: &Mnemonic) -> Result<ExtendedKey, String> {
+    ExtendedKey::master(&mnemonic.to_seed(""))?.derive(ETH_ACCOUNT_PATH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{address_to_checksum_hex, private_key_to_address_bytes};
+
+    #[test]
+    fn bip32_test_vector_1() {
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let master = ExtendedKey::master(&seed).unwrap();
+        assert_eq!(
+            hex::encode(master.secret().secret_bytes()),
+            "e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35"
+        );
+        let leaf = master.derive("m/0'/1/2'/2/1000000000").unwrap();
+        assert_eq!(
+            hex::encode(leaf.secret().secret_bytes()),
+            "471b76e389e528d6de6d816857e012c5455051cad6660850e58372a6c3e6e7c8"
+        );
+        assert_eq!(
+            leaf.address_bytes(),
+            private_key_to_address_bytes(&leaf.secret())
+        );
+        assert!(master.derive("0/1").is_err());
+        assert!(master.derive("m/2147483648").is_err());
+    }
+
+    #[test]
+    fn derives_standard_ethereum_accounts() {
+        // The well-known development mnemonic used by Hardhat and Anvil.
+        let mnemonic =
+            Mnemonic::parse("test test test test test test test test test test test junk").unwrap();
+        let parent = eth_account_parent(&mnemonic).unwrap();
+        let account =
+            |index| address_to_checksum_hex(&parent.child(index).unwrap().address_bytes());
+        assert_eq!(account(0), "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+        assert_eq!(account(1), "0x70997970C51812dc3A010C7d01b50e0d17dc79C8");
+    }
+
+    #[test]
+    fn generated_mnemonics_have_the_requested_length() {
+        for words in [12, 24] {
+            let mnemonic = generate_mnemonic(words).unwrap();
+            assert_eq!(mnemonic.word_count(), words);
+            assert_eq!(Mnemonic::parse(mnemonic.to_string()).unwrap(), mnemonic);
+        }
+        assert!(generate_mnemonic(13).is_err());
+    }
+}
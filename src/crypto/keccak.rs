@@ -0,0 +1,334 @@
+//! Multi-lane Keccak-256 specialized for hashing 64-byte public keys.
+//!
+//! Every candidate in the search is the same shape: one 64-byte `X || Y`
+//! public key, which fits in a single 136-byte Keccak-256 block. That lets us
+//! skip the general sponge entirely: load the key into the state, apply the
+//! fixed padding, run one Keccak-f\[1600\] permutation and read back the last
+//! 20 bytes of the digest.
+//!
+//! The permutation is written once over a [`Word`], one 64-bit state word
+//! per lane. On x86-64 CPUs with AVX2 a word is a 256-bit vector holding
+//! [`KECCAK_LANES`] independent states, so one pass hashes that many keys;
+//! elsewhere, and for keys left over after the last full group, it is a
+//! plain `u64` and keys are hashed one at a time.
+
+/// Number of public keys hashed together by [`addresses_from_uncompressed`]
+/// when the CPU supports AVX2.
+pub const KECCAK_LANES: usize = 4;
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000_0000_0000_0001,
+    0x0000_0000_0000_8082,
+    0x8000_0000_0000_808a,
+    0x8000_0000_8000_8000,
+    0x0000_0000_0000_808b,
+    0x0000_0000_8000_0001,
+    0x8000_0000_8000_8081,
+    0x8000_0000_0000_8009,
+    0x0000_0000_0000_008a,
+    0x0000_0000_0000_0088,
+    0x0000_0000_8000_8009,
+    0x0000_0000_8000_000a,
+    0x0000_0000_8000_808b,
+    0x8000_0000_0000_008b,
+    0x8000_0000_0000_8089,
+    0x8000_0000_0000_8003,
+    0x8000_0000_0000_8002,
+    0x8000_0000_0000_0080,
+    0x0000_0000_0000_800a,
+    0x8000_0000_8000_000a,
+    0x8000_0000_8000_8081,
+    0x8000_0000_0000_8080,
+    0x0000_0000_8000_0001,
+    0x8000_0000_8000_8008,
+];
+
+/// Rho rotation of state word `x + 5y`.
+const RHO: [u32; 25] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+/// One 64-bit Keccak state word, in one or more lanes.
+///
+/// # Safety
+///
+/// A word type may need CPU features beyond the target's baseline (AVX2
+/// for `__m256i`). Its operations may only be called once the CPU is known
+/// to support them.
+trait Word: Copy {
+    unsafe fn splat(value: u64) -> Self;
+    unsafe fn xor(self, other: Self) -> Self;
+    /// `self ^ (!b & c)`, the chi step.
+    unsafe fn chi(self, b: Self, c: Self) -> Self;
+    unsafe fn rotate_left(self, n: u32) -> Self;
+}
+
+// Plain `u64` words need no CPU features.
+impl Word for u64 {
+    #[inline(always)]
+    unsafe fn splat(value: u64) -> Self {
+        value
+    }
+
+    #[inline(always)]
+    unsafe fn xor(self, other: Self) -> Self {
+        self ^ other
+    }
+
+    #[inline(always)]
+    unsafe fn chi(self, b: Self, c: Self) -> Self {
+        self ^ (!b & c)
+    }
+
+    #[inline(always)]
+    unsafe fn rotate_left(self, n: u32) -> Self {
+        u64::rotate_left(self, n)
+    }
+}
+
+/// Repeat `$body` with `$i` bound to each listed constant. Unlike a `for`
+/// loop this guarantees every index is known at compile time, so the state
+/// stays in registers rather than being indexed through memory.
+macro_rules! unroll {
+    ($i:ident in [$($n:literal),*] $body:block) => {
+        $({
+            const $i: usize = $n;
+            $body
+        })*
+    };
+}
+
+/// Keccak-f\[1600\].
+///
+/// # Safety
+///
+/// The CPU must support `W`'s operations (see [`Word`]).
+#[inline(always)]
+unsafe fn keccak_f<W: Word>(a: &mut [W; 25]) {
+    for rc in ROUND_CONSTANTS {
+        // Theta
+        let mut c = [a[0]; 5];
+        unroll!(X in [0, 1, 2, 3, 4] {
+            c[X] = a[X].xor(a[X + 5]).xor(a[X + 10]).xor(a[X + 15]).xor(a[X + 20]);
+        });
+        let mut d = c;
+        unroll!(X in [0, 1, 2, 3, 4] {
+            d[X] = c[(X + 4) % 5].xor(c[(X + 1) % 5].rotate_left(1));
+        });
+
+        // Rho and pi: word (x, y) moves to (y, 2x + 3y).
+        let mut b = *a;
+        unroll!(I in [
+            0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12,
+            13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24
+        ] {
+            let (x, y) = (I % 5, I / 5);
+            b[y + 5 * ((2 * x + 3 * y) % 5)] = a[I].xor(d[x]).rotate_left(RHO[I]);
+        });
+
+        // Chi
+        unroll!(I in [
+            0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12,
+            13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24
+        ] {
+            let (x, y) = (I % 5, I / 5);
+            a[I] = b[I].chi(b[5 * y + (x + 1) % 5], b[5 * y + (x + 2) % 5]);
+        });
+
+        // Iota
+        a[0] = a[0].xor(W::splat(rc));
+    }
+}
+
+/// Absorb one key's worth of state words (`word(i)` for `i < 8`), apply the
+/// padding and permute.
+///
+/// # Safety
+///
+/// The CPU must support `W`'s operations (see [`Word`]).
+#[inline(always)]
+unsafe fn absorb<W: Word>(word: impl Fn(usize) -> W) -> [W; 25] {
+    let mut state = [W::splat(0); 25];
+    for (i, slot) in state.iter_mut().take(8).enumerate() {
+        *slot = word(i);
+    }
+    // Keccak padding for a 64-byte message in a 136-byte block: 0x01 right
+    // after the message, 0x80 in the last byte of the block.
+    state[8] = W::splat(0x01);
+    state[16] = W::splat(0x80 << 56);
+    keccak_f(&mut state);
+    state
+}
+
+/// Words 1 to 3 of a key's Keccak-256 digest (little-endian, as the
+/// permutation leaves them). The address is their last 20 bytes: the top
+/// half of word 1, then words 2 and 3. Matching on these directly (see
+/// [`super::WordMatcher`]) skips building the address for the candidates
+/// that fail anyway.
+pub type AddressWords = [u64; 3];
+
+/// The 20-byte address held in `words`.
+#[inline]
+pub fn address_from_words(words: &AddressWords) -> [u8; 20] {
+    let mut address = [0u8; 20];
+    address[..4].copy_from_slice(&words[0].to_le_bytes()[4..]);
+    address[4..12].copy_from_slice(&words[1].to_le_bytes());
+    address[12..].copy_from_slice(&words[2].to_le_bytes());
+    address
+}
+
+#[inline(always)]
+fn key_word(key: &[u8; 64], i: usize) -> u64 {
+    u64::from_le_bytes(key[8 * i..8 * i + 8].try_into().expect("8-byte word"))
+}
+
+/// Single-lane hash of one key.
+fn words_scalar(key: &[u8; 64]) -> AddressWords {
+    // SAFETY: `u64` words need no CPU features.
+    let state: [u64; 25] = unsafe { absorb(|i| key_word(key, i)) };
+    [state[1], state[2], state[3]]
+}
+
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    use std::arch::x86_64::*;
+
+    use super::{absorb, key_word, AddressWords, Word, KECCAK_LANES};
+
+    // Each operation is compiled for AVX2, and inlines into `hash`, which is
+    // too. Callers must have checked for AVX2, as `Word` requires.
+    impl Word for __m256i {
+        #[inline]
+        #[target_feature(enable = "avx2")]
+        unsafe fn splat(value: u64) -> Self {
+            _mm256_set1_epi64x(value as i64)
+        }
+
+        #[inline]
+        #[target_feature(enable = "avx2")]
+        unsafe fn xor(self, other: Self) -> Self {
+            _mm256_xor_si256(self, other)
+        }
+
+        #[inline]
+        #[target_feature(enable = "avx2")]
+        unsafe fn chi(self, b: Self, c: Self) -> Self {
+            _mm256_xor_si256(self, _mm256_andnot_si256(b, c))
+        }
+
+        #[inline]
+        #[target_feature(enable = "avx2")]
+        unsafe fn rotate_left(self, n: u32) -> Self {
+            // AVX2 has no 64-bit rotate. Shift counts of 64 produce zero,
+            // so `n == 0` still works.
+            let left = _mm256_sllv_epi64(self, _mm256_set1_epi64x(n as i64));
+            let right = _mm256_srlv_epi64(self, _mm256_set1_epi64x(64 - n as i64));
+            _mm256_or_si256(left, right)
+        }
+    }
+
+    /// Hash `keys` [`KECCAK_LANES`] at a time into `out[i] = emit(words)`,
+    /// returning how many were done.
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support AVX2.
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn hash<T>(
+        keys: &[[u8; 64]],
+        out: &mut [T],
+        emit: impl Fn(&AddressWords) -> T,
+    ) -> usize {
+        let groups = keys.chunks_exact(KECCAK_LANES);
+        let done = keys.len() - groups.remainder().len();
+        for (group, out) in groups.zip(out.chunks_exact_mut(KECCAK_LANES)) {
+            let [k0, k1, k2, k3] = group else {
+                unreachable!("groups hold KECCAK_LANES keys")
+            };
+            let state: [__m256i; 25] = absorb(|i| {
+                _mm256_set_epi64x(
+                    key_word(k3, i) as i64,
+                    key_word(k2, i) as i64,
+                    key_word(k1, i) as i64,
+                    key_word(k0, i) as i64,
+                )
+            });
+            let mut words = [[0u64; KECCAK_LANES]; 3];
+            for (lanes, word) in words.iter_mut().zip(&state[1..4]) {
+                _mm256_storeu_si256(lanes.as_mut_ptr().cast(), *word);
+            }
+            for (lane, slot) in out.iter_mut().enumerate() {
+                *slot = emit(&[words[0][lane], words[1][lane], words[2][lane]]);
+            }
+        }
+        done
+    }
+}
+
+fn hash_keys<T>(keys: &[[u8; 64]], out: &mut [T], emit: impl Fn(&AddressWords) -> T) {
+    assert!(out.len() >= keys.len(), "output buffer too small");
+    let mut done = 0;
+    #[cfg(target_arch = "x86_64")]
+    if std::arch::is_x86_feature_detected!("avx2") {
+        // SAFETY: the CPU supports AVX2, checked just above.
+        done = unsafe { avx2::hash(keys, out, &emit) };
+    }
+    for (key, slot) in keys[done..].iter().zip(&mut out[done..]) {
+        *slot = emit(&words_scalar(key));
+    }
+}
+
+/// Hash each uncompressed `X || Y` public key in `keys` into its 20-byte
+/// address, writing `out[i]` for `keys[i]`. Equivalent to calling
+/// [`super::uncompressed_to_address_bytes`] on each key.
+///
+/// # Panics
+///
+/// If `out` is shorter than `keys`.
+pub fn addresses_from_uncompressed(keys: &[[u8; 64]], out: &mut [[u8; 20]]) {
+    hash_keys(keys, out, address_from_words);
+}
+
+/// Like [`addresses_from_uncompressed`], but leaves each result as
+/// [`AddressWords`] for matching with [`super::WordMatcher`].
+///
+/// # Panics
+///
+/// If `out` is shorter than `keys`.
+pub fn address_words_from_uncompressed(keys: &[[u8; 64]], out: &mut [AddressWords]) {
+    hash_keys(keys, out, |words| *words);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::uncompressed_to_address_bytes;
+    use rand::RngCore;
+
+    #[test]
+    fn matches_tiny_keccak_on_random_keys() {
+        // Not a multiple of the lane count, so the scalar tail runs too.
+        let mut keys = vec![[0u8; 64]; 4 * KECCAK_LANES + 3];
+        for key in &mut keys {
+            rand::thread_rng().fill_bytes(key);
+        }
+        keys[0] = [0u8; 64];
+        keys[1] = [0xff; 64];
+
+        let mut out = vec![[0u8; 20]; keys.len()];
+        addresses_from_uncompressed(&keys, &mut out);
+        for (key, address) in keys.iter().zip(&out) {
+            assert_eq!(*address, uncompressed_to_address_bytes(key));
+        }
+    }
+
+    #[test]
+    fn scalar_path_matches_tiny_keccak() {
+        let mut key = [0u8; 64];
+        rand::thread_rng().fill_bytes(&mut key);
+        assert_eq!(
+            address_from_words(&words_scalar(&key)),
+            uncompressed_to_address_bytes(&key)
+        );
+    }
+}
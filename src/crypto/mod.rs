@@ -1,7 +1,28 @@
+use hmac::{Hmac, Mac};
 use rand::rngs::OsRng;
 use secp256k1::{PublicKey, Scalar, Secp256k1, SecretKey, SignOnly};
+use sha2::Sha256;
 use tiny_keccak::{Hasher, Keccak};
 
+mod batch;
+mod hd;
+mod keccak;
+mod pattern;
+mod trie;
+mod word_matcher;
+
+pub use batch::{BatchKeygen, KEYGEN_BATCH_SIZE};
+pub use hd::{
+    check_mnemonic_word_count, eth_account_parent, generate_mnemonic, ExtendedKey, ETH_ACCOUNT_PATH,
+};
+pub use keccak::{
+    address_from_words, address_words_from_uncompressed, addresses_from_uncompressed, AddressWords,
+    KECCAK_LANES,
+};
+pub use pattern::Pattern;
+use trie::AltTrie;
+pub use word_matcher::WordMatcher;
+
 thread_local! {
     // Secp256k1::signing_only() builds per-context state; caching per-thread
     // avoids paying that cost on every key check. We only sign, never verify.
@@ -13,14 +34,43 @@ pub fn generate_private_key() -> SecretKey {
     SecretKey::new(&mut OsRng)
 }
 
+/// Deterministic starting key for worker `worker` in reseed epoch `epoch`
+/// of a search seeded with `seed`: HMAC-SHA256 keyed by the seed.
+///
+/// **Insecure**: anyone who knows (or guesses) the seed can recompute every
+/// key, and a `u64` seed is easily brute-forced. Only for reproducible tests
+/// and benchmarks.
+pub fn seeded_private_key(seed: u64, worker: u64, epoch: u64) -> SecretKey {
+    // A digest that is zero or not below the group order (odds ~2⁻¹²⁸) is
+    // simply rehashed with the next counter.
+    (0u32..)
+        .find_map(|counter| {
+            let mut mac = Hmac::<Sha256>::new_from_slice(&seed.to_be_bytes())
+                .expect("HMAC accepts keys of any length");
+            mac.update(b"address_finder seeded key");
+            mac.update(&worker.to_be_bytes());
+            mac.update(&epoch.to_be_bytes());
+            mac.update(&counter.to_be_bytes());
+            SecretKey::from_slice(&mac.finalize().into_bytes()).ok()
+        })
+        .expect("some counter yields a valid key")
+}
+
 /// Hash a (pre-derived) secp256k1 public key into a 20-byte Ethereum address.
-fn pubkey_to_address_bytes(public_key: &PublicKey) -> [u8; 20] {
+pub fn public_key_to_address_bytes(public_key: &PublicKey) -> [u8; 20] {
     // Uncompressed serialization is 0x04 || X || Y (65 bytes); skip the tag.
-    let public_key_bytes = &public_key.serialize_uncompressed()[1..];
+    let mut xy = [0u8; 64];
+    xy.copy_from_slice(&public_key.serialize_uncompressed()[1..]);
+    uncompressed_to_address_bytes(&xy)
+}
 
+/// Hash an uncompressed public key given as `X || Y` (as produced by
+/// [`BatchKeygen`]) into a 20-byte Ethereum address.
+#[inline]
+pub fn uncompressed_to_address_bytes(xy: &[u8; 64]) -> [u8; 20] {
     let mut keccak = Keccak::v256();
     let mut hash = [0u8; 32];
-    keccak.update(public_key_bytes);
+    keccak.update(xy);
     keccak.finalize(&mut hash);
 
     let mut address = [0u8; 20];
@@ -35,7 +85,7 @@ fn pubkey_to_address_bytes(public_key: &PublicKey) -> [u8; 20] {
 /// which pays that cost once and then advances via cheap point addition.
 pub fn private_key_to_address_bytes(private_key: &SecretKey) -> [u8; 20] {
     let public_key = SECP.with(|secp| PublicKey::from_secret_key(secp, private_key));
-    pubkey_to_address_bytes(&public_key)
+    public_key_to_address_bytes(&public_key)
 }
 
 /// Iterator-like state that amortizes secp256k1 scalar multiplication across
@@ -44,62 +94,59 @@ pub fn private_key_to_address_bytes(private_key: &SecretKey) -> [u8; 20] {
 /// Starts from a uniformly-random secret `k₀` and its public point `P₀ = k₀·G`
 /// (one scalar multiplication at construction), then each call to
 /// [`IncrementalKeygen::advance`] steps to `k_{i+1} = k_i + 1` and
-/// `P_{i+1} = P_i + G` using a single point addition — roughly an order of
-/// magnitude cheaper than a fresh scalar multiplication.
+/// `P_{i+1} = P_i + G`. The points are computed [`KEYGEN_BATCH_SIZE`] at a
+/// time by [`BatchKeygen`], which shares one field inversion across the
+/// batch; hot loops that can consume a whole batch should use it directly.
 ///
 /// Keys produced this way are still unpredictable to an adversary: the
 /// starting point is fresh entropy, and knowing the final key reveals the
 /// starting one, which is a group element sampled from a full 256-bit
 /// space. This is the standard technique used by vanity-address tools.
 pub struct IncrementalKeygen {
-    secret: SecretKey,
-    public: PublicKey,
-    g_public: PublicKey,
-    one_tweak: Scalar,
+    batch: BatchKeygen,
+    index: usize,
 }
 
 impl IncrementalKeygen {
     /// Seed from a fresh random `k₀`, performing one scalar multiplication.
     pub fn new() -> Self {
-        let secret = SecretKey::new(&mut OsRng);
-        let public = SECP.with(|secp| PublicKey::from_secret_key(secp, &secret));
-
-        // G, stored as a PublicKey so we can use pure point-addition to step.
-        let mut one_bytes = [0u8; 32];
-        one_bytes[31] = 1;
-        let one_secret = SecretKey::from_slice(&one_bytes).expect("1 is a valid secp256k1 scalar");
-        let g_public = SECP.with(|secp| PublicKey::from_secret_key(secp, &one_secret));
-        let one_tweak = Scalar::from(one_secret);
-
-        Self {
-            secret,
-            public,
-            g_public,
-            one_tweak,
-        }
+        Self::from_secret(generate_private_key())
+    }
+
+    /// Start the walk at `k₀ = secret`, e.g. a [`seeded_private_key`].
+    pub fn from_secret(secret: SecretKey) -> Self {
+        let mut batch = BatchKeygen::from_secret(secret);
+        batch.next_batch();
+        Self { batch, index: 0 }
     }
 
-    /// Current private key (corresponds to [`Self::address_bytes`]).
+    /// Walk `public + k·G` from `k = offset` for a split-key search: the
+    /// searcher never learns the private key, and [`Self::secret`] returns
+    /// the offset `k` that [`combine_split_key`] later adds to it.
+    pub fn from_public_offset(public: &PublicKey, offset: SecretKey) -> Self {
+        let mut batch = BatchKeygen::from_public_offset(public, offset);
+        batch.next_batch();
+        Self { batch, index: 0 }
+    }
+
+    /// Current private key (corresponds to [`Self::address_bytes`]), or the
+    /// current offset for [`Self::from_public_offset`].
     pub fn secret(&self) -> SecretKey {
-        self.secret
+        self.batch.secret(self.index)
     }
 
     /// Keccak-256 of the current uncompressed public key, truncated to 20 bytes.
     pub fn address_bytes(&self) -> [u8; 20] {
-        pubkey_to_address_bytes(&self.public)
+        uncompressed_to_address_bytes(&self.batch.public_keys()[self.index])
     }
 
-    /// Step to `k+1` / `P+G`. Cost: one point addition + one scalar add mod n.
+    /// Step to `k+1` / `P+G`, computing the next batch when this one runs out.
     pub fn advance(&mut self) {
-        // Overflow past the group order n ≈ 2²⁵⁶ is not reachable in practice.
-        self.secret = self
-            .secret
-            .add_tweak(&self.one_tweak)
-            .expect("scalar order overflow is unreachable");
-        self.public = self
-            .public
-            .combine(&self.g_public)
-            .expect("P + G is never the identity for P ≠ -G");
+        self.index += 1;
+        if self.index == KEYGEN_BATCH_SIZE {
+            self.batch.next_batch();
+            self.index = 0;
+        }
     }
 }
 
@@ -109,11 +156,73 @@ impl Default for IncrementalKeygen {
     }
 }
 
+/// Private key for a split-key search result: the user's `secret` plus the
+/// `partial` key the searcher found for its public key.
+pub fn combine_split_key(secret: &SecretKey, partial: &SecretKey) -> Result<SecretKey, String> {
+    secret
+        .add_tweak(&Scalar::from(*partial))
+        .map_err(|_| "partial key cancels out the secret key".to_string())
+}
+
 /// Format a 20-byte address as `0x<40 hex chars>`.
 pub fn address_to_hex(address: &[u8; 20]) -> String {
     format!("0x{}", hex::encode(address))
 }
 
+/// Format a 20-byte address in its EIP-55 mixed-case checksum form, as shown
+/// by wallets and block explorers (e.g. `0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed`).
+pub fn address_to_checksum_hex(address: &[u8; 20]) -> String {
+    let lower = lower_hex(address);
+    let upper_bits = checksum_upper_bits(&lower);
+    let mut out = String::with_capacity(42);
+    out.push_str("0x");
+    for (i, &c) in lower.iter().enumerate() {
+        if upper_bits & (1 << i) != 0 {
+            out.push(c.to_ascii_uppercase() as char);
+        } else {
+            out.push(c as char);
+        }
+    }
+    out
+}
+
+/// Lowercase ASCII hex of an address, written to a stack buffer so the
+/// checksum path stays allocation-free.
+#[inline]
+fn lower_hex(address: &[u8; 20]) -> [u8; 40] {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut out = [0u8; 40];
+    for (i, &byte) in address.iter().enumerate() {
+        out[2 * i] = DIGITS[(byte >> 4) as usize];
+        out[2 * i + 1] = DIGITS[(byte & 0x0f) as usize];
+    }
+    out
+}
+
+/// EIP-55: hash the lowercase hex address and uppercase every letter whose
+/// corresponding hash nibble is >= 8. Bit `i` of the result is set when
+/// nibble `i` of the checksummed form is uppercase (digits never are).
+#[inline]
+fn checksum_upper_bits(lower: &[u8; 40]) -> u64 {
+    let mut keccak = Keccak::v256();
+    let mut hash = [0u8; 32];
+    keccak.update(lower);
+    keccak.finalize(&mut hash);
+
+    let mut bits = 0u64;
+    for (i, &c) in lower.iter().enumerate() {
+        let hash_nibble = if i % 2 == 0 {
+            hash[i / 2] >> 4
+        } else {
+            hash[i / 2] & 0x0f
+        };
+        if c.is_ascii_alphabetic() && hash_nibble >= 8 {
+            bits |= 1 << i;
+        }
+    }
+    bits
+}
+
 /// Derive Ethereum address from private key, returning the hex form.
 ///
 /// Convenience wrapper around [`private_key_to_address_bytes`] + [`address_to_hex`].
@@ -122,14 +231,160 @@ pub fn private_key_to_address(private_key: &SecretKey) -> String {
     address_to_hex(&private_key_to_address_bytes(private_key))
 }
 
+/// Address of a contract deployed with `CREATE` by `sender` at `nonce`:
+/// `keccak(rlp([sender, nonce]))[12..]`.
+///
+/// The RLP payload is at most 30 bytes (a 21-byte string plus a 9-byte
+/// integer), so it is built on the stack with a single short-list header.
+pub fn contract_address_bytes(sender: &[u8; 20], nonce: u64) -> [u8; 20] {
+    let mut rlp = [0u8; 31];
+    rlp[1] = 0x80 + 20;
+    rlp[2..22].copy_from_slice(sender);
+    let nonce_len = rlp_encode_u64(nonce, &mut rlp[22..]);
+    let payload_len = 21 + nonce_len;
+    rlp[0] = 0xc0 + payload_len as u8;
+
+    let mut keccak = Keccak::v256();
+    let mut hash = [0u8; 32];
+    keccak.update(&rlp[..1 + payload_len]);
+    keccak.finalize(&mut hash);
+
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    address
+}
+
+/// Address of a contract deployed with `CREATE2`:
+/// `keccak(0xff ++ deployer ++ salt ++ init_code_hash)[12..]` (EIP-1014).
+pub fn create2_address_bytes(
+    deployer: &[u8; 20],
+    salt: &[u8; 32],
+    init_code_hash: &[u8; 32],
+) -> [u8; 20] {
+    Create2Hasher::new(deployer, salt, init_code_hash).address_bytes()
+}
+
+/// Keccak-256 of arbitrary bytes, e.g. contract init code for `CREATE2`.
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut keccak = Keccak::v256();
+    let mut hash = [0u8; 32];
+    keccak.update(data);
+    keccak.finalize(&mut hash);
+    hash
+}
+
+/// Salt iterator for `CREATE2` mining.
+///
+/// Keeps the full 85-byte preimage `0xff ++ deployer ++ salt ++ init_code_hash`
+/// in place and treats the last 8 bytes of the salt as a big-endian counter,
+/// so [`Create2Hasher::advance`] only rewrites those bytes and each candidate
+/// costs exactly one Keccak-256.
+pub struct Create2Hasher {
+    preimage: [u8; 85],
+}
+
+impl Create2Hasher {
+    const SALT: std::ops::Range<usize> = 21..53;
+    const COUNTER: std::ops::Range<usize> = 45..53;
+
+    /// Start from `salt` for the given deployer and init-code hash.
+    pub fn new(deployer: &[u8; 20], salt: &[u8; 32], init_code_hash: &[u8; 32]) -> Self {
+        let mut preimage = [0u8; 85];
+        preimage[0] = 0xff;
+        preimage[1..21].copy_from_slice(deployer);
+        preimage[Self::SALT].copy_from_slice(salt);
+        preimage[53..].copy_from_slice(init_code_hash);
+        Self { preimage }
+    }
+
+    /// Current salt (corresponds to [`Self::address_bytes`]).
+    pub fn salt(&self) -> [u8; 32] {
+        let mut salt = [0u8; 32];
+        salt.copy_from_slice(&self.preimage[Self::SALT]);
+        salt
+    }
+
+    /// `CREATE2` address for the current salt.
+    #[inline]
+    pub fn address_bytes(&self) -> [u8; 20] {
+        let hash = keccak256(&self.preimage);
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&hash[12..]);
+        address
+    }
+
+    /// Step the salt counter by one, wrapping within its low 8 bytes.
+    #[inline]
+    pub fn advance(&mut self) {
+        let mut counter = [0u8; 8];
+        counter.copy_from_slice(&self.preimage[Self::COUNTER]);
+        let next = u64::from_be_bytes(counter).wrapping_add(1);
+        self.preimage[Self::COUNTER].copy_from_slice(&next.to_be_bytes());
+    }
+}
+
+/// RLP-encode an integer into `out`, returning the number of bytes written.
+/// Zero is the empty string (`0x80`), values below `0x80` are a single byte,
+/// and everything else is a length-prefixed big-endian byte string.
+fn rlp_encode_u64(value: u64, out: &mut [u8]) -> usize {
+    if value == 0 {
+        out[0] = 0x80;
+        return 1;
+    }
+    if value < 0x80 {
+        out[0] = value as u8;
+        return 1;
+    }
+    let be = value.to_be_bytes();
+    let skip = (value.leading_zeros() / 8) as usize;
+    let len = 8 - skip;
+    out[0] = 0x80 + len as u8;
+    out[1..=len].copy_from_slice(&be[skip..]);
+    1 + len
+}
+
+/// A single decoded prefix or suffix alternative.
+///
+/// `upper` / `lower` are bitmasks over the 40 absolute nibble positions of
+/// the address: a set bit means that letter must be uppercase (resp.
+/// lowercase) in the EIP-55 rendering. Both are zero unless the rule was
+/// built with [`MatchRule::new_case_sensitive`], so plain nibble searches
+/// never pay for the extra Keccak.
+#[derive(Debug, Clone)]
+struct Alt {
+    nibbles: Vec<u8>,
+    upper: u64,
+    lower: u64,
+}
+
+impl Alt {
+    #[inline]
+    fn has_case(&self) -> bool {
+        (self.upper | self.lower) != 0
+    }
+}
+
 /// Pre-decoded prefix/suffix alternatives, so the hot loop never re-lowercases
 /// or re-decodes anything per candidate. Each side holds zero-or-more
 /// alternatives; an address matches if it satisfies *any* prefix alternative
 /// AND *any* suffix alternative (with an empty list meaning "no constraint").
+/// Optional [`Pattern`]s and unanchored infixes add further constraints of
+/// the same shape: *any* alternative of each must match as well. Each
+/// side's alternatives are compiled into a nibble trie, so matching costs
+/// the same for thousands of alternatives as for one.
 #[derive(Debug, Clone)]
 pub struct MatchRule {
-    prefix_alts: Vec<Vec<u8>>,
-    suffix_alts: Vec<Vec<u8>>,
+    prefix_alts: Vec<Alt>,
+    suffix_alts: Vec<Alt>,
+    patterns: Vec<Pattern>,
+    case_sensitive: bool,
+    prefix_trie: AltTrie,
+    suffix_trie: AltTrie,
+    /// Alternatives that may appear anywhere, walked from every offset of
+    /// `infix_trie`.
+    infix_alts: Vec<Alt>,
+    infix_trie: AltTrie,
+    words: WordMatcher,
 }
 
 impl MatchRule {
@@ -137,65 +392,297 @@ impl MatchRule {
     /// An empty slice means "no constraint on this side". Non-hex characters
     /// or overly long inputs return an error.
     pub fn new<S: AsRef<str>>(prefixes: &[S], suffixes: &[S]) -> Result<Self, String> {
-        let prefix_alts = decode_alts(prefixes)?;
-        let suffix_alts = decode_alts(suffixes)?;
-        let max_prefix = prefix_alts.iter().map(Vec::len).max().unwrap_or(0);
-        let max_suffix = suffix_alts.iter().map(Vec::len).max().unwrap_or(0);
+        Self::build(prefixes, suffixes, false)
+    }
+
+    /// Like [`MatchRule::new`], but letters in the patterns must also match
+    /// the case of the EIP-55 checksummed address: `DeAd` only accepts
+    /// addresses that display as `0xDeAd...` in wallets. Digits are
+    /// unaffected. Candidates that pass the nibble check are hashed once more
+    /// to compute the checksum, so the common rejection path is unchanged.
+    pub fn new_case_sensitive<S: AsRef<str>>(
+        prefixes: &[S],
+        suffixes: &[S],
+    ) -> Result<Self, String> {
+        Self::build(prefixes, suffixes, true)
+    }
+
+    fn build<S: AsRef<str>>(
+        prefixes: &[S],
+        suffixes: &[S],
+        case_sensitive: bool,
+    ) -> Result<Self, String> {
+        let prefix_alts = decode_alts(prefixes, Side::Prefix, case_sensitive)?;
+        let suffix_alts = decode_alts(suffixes, Side::Suffix, case_sensitive)?;
+        let max_prefix = prefix_alts
+            .iter()
+            .map(|a| a.nibbles.len())
+            .max()
+            .unwrap_or(0);
+        let max_suffix = suffix_alts
+            .iter()
+            .map(|a| a.nibbles.len())
+            .max()
+            .unwrap_or(0);
         if max_prefix + max_suffix > 40 {
             return Err(format!(
                 "prefix + suffix length exceeds 40 hex chars (got {max_prefix} + {max_suffix})"
             ));
         }
         Ok(Self {
+            prefix_trie: AltTrie::new(&prefix_alts, Side::Prefix),
+            suffix_trie: AltTrie::new(&suffix_alts, Side::Suffix),
+            words: WordMatcher::new(&prefix_alts, &suffix_alts),
             prefix_alts,
             suffix_alts,
+            infix_alts: Vec::new(),
+            infix_trie: AltTrie::new(&[], Side::Prefix),
+            patterns: Vec::new(),
+            case_sensitive,
         })
     }
 
+    /// Additionally require the address to match one of `patterns` (see
+    /// [`Pattern`] for the syntax). Patterns inherit the rule's case
+    /// sensitivity. An empty slice leaves the rule unchanged.
+    pub fn with_patterns<S: AsRef<str>>(mut self, patterns: &[S]) -> Result<Self, String> {
+        for p in patterns {
+            self.patterns
+                .push(Pattern::parse(p.as_ref(), self.case_sensitive)?);
+        }
+        Ok(self)
+    }
+
+    /// Additionally require the address to contain one of `infixes` (hex
+    /// strings) at any position, e.g. the spellings of a word list. They are
+    /// matched through a trie like prefixes, so large sets are cheap. Infixes
+    /// match on nibbles only, even in a case-sensitive rule. An empty slice
+    /// leaves the rule unchanged.
+    pub fn with_infixes<S: AsRef<str>>(mut self, infixes: &[S]) -> Result<Self, String> {
+        for infix in infixes {
+            let nibbles = decode_nibbles(infix.as_ref())?;
+            if nibbles.is_empty() || nibbles.len() > 40 {
+                return Err(format!(
+                    "infix must be 1 to 40 hex chars (got {:?})",
+                    infix.as_ref()
+                ));
+            }
+            self.infix_alts.push(Alt {
+                nibbles,
+                upper: 0,
+                lower: 0,
+            });
+        }
+        self.infix_trie = AltTrie::new(&self.infix_alts, Side::Prefix);
+        Ok(self)
+    }
+
+    /// Whether letters must match the EIP-55 checksum case.
+    pub fn is_case_sensitive(&self) -> bool {
+        self.case_sensitive
+    }
+
+    /// The prefix/suffix alternatives compiled for testing raw Keccak output,
+    /// so most candidates are rejected before their address is built.
+    pub fn word_matcher(&self) -> &WordMatcher {
+        &self.words
+    }
+
+    /// Probability that a uniformly random address matches the rule.
+    ///
+    /// Exact for prefix/suffix alternatives, including overlapping ones such
+    /// as `de` and `dead` (which only count once). Each checksum case
+    /// constraint counts as a fair coin flip. Unanchored patterns are
+    /// approximated by treating each offset they can match at, and each
+    /// pattern alternative, as independent; so are infixes.
+    pub fn match_probability(&self) -> f64 {
+        let patterns = if self.patterns.is_empty() {
+            1.0
+        } else {
+            1.0 - self
+                .patterns
+                .iter()
+                .map(|p| 1.0 - p.match_probability())
+                .product::<f64>()
+        };
+        let infixes = if self.infix_alts.is_empty() {
+            1.0
+        } else {
+            // ln P(no infix anywhere), summed over every (infix, offset).
+            let ln_miss: f64 = self
+                .infix_alts
+                .iter()
+                .map(|alt| {
+                    let len = alt.nibbles.len();
+                    (41 - len) as f64 * (-16f64.powi(-(len as i32))).ln_1p()
+                })
+                .sum();
+            -ln_miss.exp_m1()
+        };
+        alts_probability(&self.prefix_alts, Side::Prefix)
+            * alts_probability(&self.suffix_alts, Side::Suffix)
+            * infixes
+            * patterns
+    }
+
     /// Returns true if `address` matches one of the prefix alternatives (or
     /// no prefix is required) AND one of the suffix alternatives (or none is
-    /// required).
+    /// required) AND one of the infixes anywhere (or none is required) AND
+    /// one of the patterns (or none is required).
     #[inline]
     pub fn matches(&self, address: &[u8; 20]) -> bool {
+        // Checksum bits are computed at most once per candidate, and only if
+        // some case-constrained alternative already matched on nibbles.
+        let mut upper_bits = None;
         let prefix_ok = self.prefix_alts.is_empty()
-            || self
-                .prefix_alts
-                .iter()
-                .any(|alt| matches_prefix(address, alt));
+            || self.prefix_trie.matches(address, |i| {
+                case_ok(address, &self.prefix_alts[i], &mut upper_bits)
+            });
         if !prefix_ok {
             return false;
         }
-        self.suffix_alts.is_empty()
-            || self
-                .suffix_alts
-                .iter()
-                .any(|alt| matches_suffix(address, alt))
+        let suffix_ok = self.suffix_alts.is_empty()
+            || self.suffix_trie.matches(address, |i| {
+                case_ok(address, &self.suffix_alts[i], &mut upper_bits)
+            });
+        if !suffix_ok {
+            return false;
+        }
+        if !self.infix_alts.is_empty()
+            && !(0..40).any(|offset| self.infix_trie.matches_at(address, offset, |_| true))
+        {
+            return false;
+        }
+        if self.patterns.is_empty() {
+            return true;
+        }
+        let nibbles = address_to_nibbles(address);
+        self.patterns
+            .iter()
+            .any(|p| p.matches(address, &nibbles, &mut upper_bits))
     }
 }
 
 #[inline]
-fn matches_prefix(address: &[u8; 20], alt: &[u8]) -> bool {
-    for (i, &want) in alt.iter().enumerate() {
-        if nibble_at(address, i) != want {
-            return false;
-        }
+fn case_ok(address: &[u8; 20], alt: &Alt, upper_bits: &mut Option<u64>) -> bool {
+    if !alt.has_case() {
+        return true;
     }
-    true
+    let bits = checksum_bits(address, upper_bits);
+    bits & alt.upper == alt.upper && bits & alt.lower == 0
 }
 
+/// EIP-55 uppercase bits for `address`, computed on first use and cached in
+/// `upper_bits` for the rest of the candidate's checks.
 #[inline]
-fn matches_suffix(address: &[u8; 20], alt: &[u8]) -> bool {
-    let len = alt.len();
-    for (i, &want) in alt.iter().enumerate() {
-        if nibble_at(address, 40 - len + i) != want {
-            return false;
-        }
+fn checksum_bits(address: &[u8; 20], upper_bits: &mut Option<u64>) -> u64 {
+    *upper_bits.get_or_insert_with(|| checksum_upper_bits(&lower_hex(address)))
+}
+
+/// Split an address into its 40 nibbles, most significant first.
+#[inline]
+fn address_to_nibbles(address: &[u8; 20]) -> [u8; 40] {
+    let mut out = [0u8; 40];
+    for (i, &byte) in address.iter().enumerate() {
+        out[2 * i] = byte >> 4;
+        out[2 * i + 1] = byte & 0x0f;
     }
-    true
+    out
 }
 
-fn decode_alts<S: AsRef<str>>(alts: &[S]) -> Result<Vec<Vec<u8>>, String> {
-    alts.iter().map(|s| decode_nibbles(s.as_ref())).collect()
+/// Probability that an address matches any of `alts` (1.0 if there are none).
+fn alts_probability(alts: &[Alt], side: Side) -> f64 {
+    if alts.is_empty() {
+        return 1.0;
+    }
+    // Spell each alternative as (nibble, case) symbols walking inwards from
+    // its anchor, so shared leading symbols form a trie.
+    let mut spelled: Vec<Vec<(u8, u8)>> = alts
+        .iter()
+        .map(|alt| {
+            let len = alt.nibbles.len();
+            let symbol = |i: usize| {
+                let pos = match side {
+                    Side::Prefix => i,
+                    Side::Suffix => 40 - len + i,
+                };
+                let case = (alt.upper >> pos & 1) as u8 | ((alt.lower >> pos & 1) as u8) << 1;
+                (alt.nibbles[i], case)
+            };
+            match side {
+                Side::Prefix => (0..len).map(symbol).collect(),
+                Side::Suffix => (0..len).rev().map(symbol).collect(),
+            }
+        })
+        .collect();
+    spelled.sort();
+    spelled.dedup();
+    let spelled: Vec<&[(u8, u8)]> = spelled.iter().map(Vec::as_slice).collect();
+    trie_probability(&spelled)
+}
+
+/// Probability of matching any of the sorted symbol strings `alts`. Distinct
+/// symbols at one position are disjoint events, so the union is a sum over
+/// the first symbol of the probability of matching any of its tails.
+fn trie_probability(alts: &[&[(u8, u8)]]) -> f64 {
+    if alts.iter().any(|a| a.is_empty()) {
+        return 1.0;
+    }
+    let mut total = 0.0;
+    let mut i = 0;
+    while i < alts.len() {
+        let (nibble, case) = alts[i][0];
+        let group_len = alts[i..]
+            .iter()
+            .take_while(|a| a[0] == (nibble, case))
+            .count();
+        let tails: Vec<&[(u8, u8)]> = alts[i..i + group_len].iter().map(|a| &a[1..]).collect();
+        let symbol = if case == 0 { 1.0 / 16.0 } else { 1.0 / 32.0 };
+        total += symbol * trie_probability(&tails);
+        i += group_len;
+    }
+    total
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Side {
+    Prefix,
+    Suffix,
+}
+
+fn decode_alts<S: AsRef<str>>(
+    alts: &[S],
+    side: Side,
+    case_sensitive: bool,
+) -> Result<Vec<Alt>, String> {
+    alts.iter()
+        .map(|s| decode_alt(s.as_ref(), side, case_sensitive))
+        .collect()
+}
+
+fn decode_alt(s: &str, side: Side, case_sensitive: bool) -> Result<Alt, String> {
+    let nibbles = decode_nibbles(s)?;
+    let mut upper = 0u64;
+    let mut lower = 0u64;
+    if case_sensitive && nibbles.len() <= 40 {
+        let trimmed = s.strip_prefix("0x").unwrap_or(s);
+        let base = match side {
+            Side::Prefix => 0,
+            Side::Suffix => 40 - nibbles.len(),
+        };
+        for (i, c) in trimmed.chars().enumerate() {
+            if c.is_ascii_uppercase() {
+                upper |= 1 << (base + i);
+            } else if c.is_ascii_lowercase() {
+                lower |= 1 << (base + i);
+            }
+        }
+    }
+    Ok(Alt {
+        nibbles,
+        upper,
+        lower,
+    })
 }
 
 #[inline]
@@ -224,6 +711,44 @@ fn decode_nibbles(s: &str) -> Result<Vec<u8>, String> {
     Ok(out)
 }
 
+/// What [`ZeroByteScorer`] counts. Zero bytes are cheaper in calldata (4 gas
+/// vs 16), and leading zero bytes additionally allow shorter `PUSH` opcodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ZeroMetric {
+    /// Number of `0x00` bytes at the start of the address.
+    #[default]
+    Leading,
+    /// Number of `0x00` bytes anywhere in the address.
+    Total,
+}
+
+/// Scoring counterpart to [`MatchRule`] for "gas-efficient" addresses: rather
+/// than a yes/no match, every candidate gets a zero-byte count and the search
+/// keeps whichever scores highest.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ZeroByteScorer {
+    metric: ZeroMetric,
+}
+
+impl ZeroByteScorer {
+    pub fn new(metric: ZeroMetric) -> Self {
+        Self { metric }
+    }
+
+    pub fn metric(&self) -> ZeroMetric {
+        self.metric
+    }
+
+    /// Number of zero bytes in `address` according to the configured metric.
+    #[inline]
+    pub fn score(&self, address: &[u8; 20]) -> u32 {
+        match self.metric {
+            ZeroMetric::Leading => address.iter().take_while(|&&b| b == 0).count() as u32,
+            ZeroMetric::Total => address.iter().filter(|&&b| b == 0).count() as u32,
+        }
+    }
+}
+
 /// Back-compat: check if an address (hex string form) matches the prefix/suffix.
 ///
 /// Allocates; prefer [`MatchRule::matches`] in hot code.
@@ -358,6 +883,69 @@ mod tests {
         assert!(MatchRule::new(&[p.as_str()], &[s.as_str()]).is_ok());
     }
 
+    #[test]
+    fn large_alternative_sets_match_like_a_linear_scan() {
+        use rand::{Rng, RngCore};
+        let mut rng = rand::thread_rng();
+        let mut random_hex = |len: usize| -> String {
+            (0..len)
+                .map(|_| char::from_digit(rng.gen_range(0..16), 16).unwrap())
+                .collect()
+        };
+        let prefixes: Vec<String> = (0..5000).map(|i| random_hex(3 + i % 4)).collect();
+        let suffixes: Vec<String> = (0..500).map(|i| random_hex(1 + i % 3)).collect();
+        let rule = MatchRule::new(&prefixes, &suffixes).unwrap();
+
+        let mut rng = rand::thread_rng();
+        let mut hits = 0;
+        for i in 0..20_000 {
+            let mut address = [0u8; 20];
+            rng.fill_bytes(&mut address);
+            if i % 2 == 0 {
+                // Plant a prefix so both outcomes are well covered.
+                let planted = hex::decode(format!("{:0<8}", prefixes[i % 5000])).unwrap();
+                address[..3].copy_from_slice(&planted[..3]);
+            }
+            let hex = hex::encode(address);
+            let want = prefixes.iter().any(|p| hex.starts_with(p.as_str()))
+                && suffixes.iter().any(|s| hex.ends_with(s.as_str()));
+            assert_eq!(rule.matches(&address), want, "{hex}");
+            hits += want as usize;
+
+            let mut words = [0u64; 3];
+            words[0] = u64::from(u32::from_le_bytes(address[..4].try_into().unwrap())) << 32;
+            words[1] = u64::from_le_bytes(address[4..12].try_into().unwrap());
+            words[2] = u64::from_le_bytes(address[12..].try_into().unwrap());
+            assert_eq!(address_from_words(&words), address);
+            if want {
+                assert!(rule.word_matcher().may_match(&words), "{hex}");
+            }
+        }
+        assert!(hits > 100, "only {hits} matches");
+    }
+
+    #[test]
+    fn infixes_match_at_any_offset() {
+        let rule = MatchRule::new(&[] as &[&str], &[] as &[&str])
+            .unwrap()
+            .with_infixes(&["c0ffee", "cafe"])
+            .unwrap();
+        for offset in 0..=34 {
+            let mut hex = "1".repeat(40);
+            hex.replace_range(offset..offset + 6, "c0ffee");
+            let address: [u8; 20] = hex::decode(&hex).unwrap().try_into().unwrap();
+            assert!(rule.matches(&address), "{hex}");
+        }
+        assert!(!rule.matches(&[0x11; 20]));
+        assert!(!rule.matches(&addr_from_hex("c0ffe00000000000000000000000000000000caf")));
+        assert!(rule.match_probability() > 36.0 / 16f64.powi(4));
+        assert!(rule.match_probability() < 38.0 / 16f64.powi(4));
+        assert!(MatchRule::new(&[] as &[&str], &[] as &[&str])
+            .unwrap()
+            .with_infixes(&["xyz"])
+            .is_err());
+    }
+
     #[test]
     fn incremental_keygen_matches_fresh_derivation() {
         // Take a snapshot of (secret_i, address_i) from the incremental
@@ -377,6 +965,53 @@ mod tests {
         }
     }
 
+    #[test]
+    fn seeded_keys_are_reproducible_per_worker_and_epoch() {
+        let key = seeded_private_key(42, 0, 0);
+        assert_eq!(key, seeded_private_key(42, 0, 0));
+        for other in [
+            seeded_private_key(43, 0, 0),
+            seeded_private_key(42, 1, 0),
+            seeded_private_key(42, 0, 1),
+        ] {
+            assert_ne!(key, other);
+        }
+        let mut kg = IncrementalKeygen::from_secret(key);
+        assert_eq!(kg.secret(), key);
+        kg.advance();
+        assert_eq!(
+            kg.address_bytes(),
+            private_key_to_address_bytes(&kg.secret())
+        );
+    }
+
+    #[test]
+    fn split_key_search_combines_into_the_matching_key() {
+        let user = generate_private_key();
+        let public = SECP.with(|secp| PublicKey::from_secret_key(secp, &user));
+        let mut kg = IncrementalKeygen::from_public_offset(&public, generate_private_key());
+        for _ in 0..3 {
+            kg.advance();
+        }
+        let combined = combine_split_key(&user, &kg.secret()).unwrap();
+        assert_eq!(private_key_to_address_bytes(&combined), kg.address_bytes());
+    }
+
+    #[test]
+    fn incremental_keygen_crosses_batch_boundaries() {
+        let mut kg = IncrementalKeygen::new();
+        for _ in 0..KEYGEN_BATCH_SIZE - 2 {
+            kg.advance();
+        }
+        for _ in 0..4 {
+            assert_eq!(
+                kg.address_bytes(),
+                private_key_to_address_bytes(&kg.secret())
+            );
+            kg.advance();
+        }
+    }
+
     #[test]
     fn match_rule_accepts_0x_prefix() {
         let rule = MatchRule::new(&one("0xab"), NONE).unwrap();
@@ -384,4 +1019,196 @@ mod tests {
         addr[0] = 0xab;
         assert!(rule.matches(&addr));
     }
+
+    fn addr_from_hex(s: &str) -> [u8; 20] {
+        let mut out = [0u8; 20];
+        hex::decode_to_slice(s.strip_prefix("0x").unwrap_or(s).to_lowercase(), &mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn checksum_hex_matches_eip55_vectors() {
+        // Test vectors from the EIP-55 specification.
+        for expected in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            assert_eq!(address_to_checksum_hex(&addr_from_hex(expected)), expected);
+        }
+    }
+
+    #[test]
+    fn case_sensitive_rule_checks_checksum_case() {
+        let addr = addr_from_hex("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed");
+
+        // Right case on both sides.
+        let rule = MatchRule::new_case_sensitive(&one("5aAeb"), &one("BeAed")).unwrap();
+        assert!(rule.matches(&addr));
+
+        // Same nibbles, wrong case: only the case-insensitive rule accepts.
+        let rule = MatchRule::new_case_sensitive(&one("5AAEB"), NONE).unwrap();
+        assert!(!rule.matches(&addr));
+        assert!(MatchRule::new(&one("5AAEB"), NONE).unwrap().matches(&addr));
+
+        // Lowercase letters in the pattern require lowercase in the checksum.
+        let rule = MatchRule::new_case_sensitive(NONE, &one("beaed")).unwrap();
+        assert!(!rule.matches(&addr));
+
+        // Digits carry no case and still match.
+        let rule = MatchRule::new_case_sensitive(&one("5"), NONE).unwrap();
+        assert!(rule.matches(&addr));
+    }
+
+    #[test]
+    fn case_sensitive_rule_tries_every_alt() {
+        // The first alt matches on nibbles but not on case; the second one
+        // must still be considered.
+        let addr = addr_from_hex("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed");
+        let rule = MatchRule::new_case_sensitive(&["5AA", "5aA"], NONE).unwrap();
+        assert!(rule.matches(&addr));
+    }
+
+    #[test]
+    fn contract_address_matches_known_deployments() {
+        // Well-known CREATE addresses for deployer 0x6ac7…dbf0.
+        let sender = addr_from_hex("0x6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0");
+        for (nonce, expected) in [
+            (0, "0xcd234a471b72ba2f1ccf0a70fcaba648a5eecd8d"),
+            (1, "0x343c43a37d37dff08ae8c4a11544c718abb4fcf8"),
+            (2, "0xf778b86fa74e846c4f0a1fbd1335fe81c00a0c91"),
+            (3, "0xfffd933a0bc612844eaf0c6fe3e5b8e9b6c1d19c"),
+        ] {
+            assert_eq!(
+                address_to_hex(&contract_address_bytes(&sender, nonce)),
+                expected,
+                "nonce {nonce}"
+            );
+        }
+    }
+
+    #[test]
+    fn rlp_encodes_nonce_boundaries() {
+        let mut out = [0u8; 9];
+        assert_eq!(rlp_encode_u64(0, &mut out), 1);
+        assert_eq!(out[0], 0x80);
+        assert_eq!(rlp_encode_u64(0x7f, &mut out), 1);
+        assert_eq!(out[0], 0x7f);
+        assert_eq!(rlp_encode_u64(0x80, &mut out), 2);
+        assert_eq!(&out[..2], &[0x81, 0x80]);
+        assert_eq!(rlp_encode_u64(0x0400, &mut out), 3);
+        assert_eq!(&out[..3], &[0x82, 0x04, 0x00]);
+        assert_eq!(rlp_encode_u64(u64::MAX, &mut out), 9);
+        assert_eq!(out, [0x88, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+    }
+
+    #[test]
+    fn create2_address_matches_eip1014_vectors() {
+        // Examples 0, 1 and 5 from EIP-1014.
+        for (deployer, salt, init_code, expected) in [
+            (
+                "0000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "00",
+                "0x4D1A2e2bB4F88F0250f26Ffff098B0b30B26BF38",
+            ),
+            (
+                "deadbeef00000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "00",
+                "0xB928f69Bb1D91Cd65274e3c79d8986362984fDA3",
+            ),
+            (
+                "00000000000000000000000000000000deadbeef",
+                "00000000000000000000000000000000000000000000000000000000cafebabe",
+                "deadbeef",
+                "0x60f3f640a8508fC6a86d45DF051962668E1e8AC7",
+            ),
+        ] {
+            let deployer = addr_from_hex(deployer);
+            let mut salt_bytes = [0u8; 32];
+            hex::decode_to_slice(salt, &mut salt_bytes).unwrap();
+            let init_code_hash = keccak256(&hex::decode(init_code).unwrap());
+            let address = create2_address_bytes(&deployer, &salt_bytes, &init_code_hash);
+            assert_eq!(address_to_checksum_hex(&address), expected);
+        }
+    }
+
+    #[test]
+    fn create2_hasher_advance_increments_salt_counter() {
+        let mut salt = [0u8; 32];
+        salt[31] = 0xff;
+        let mut hasher = Create2Hasher::new(&[1u8; 20], &salt, &[2u8; 32]);
+        hasher.advance();
+        let next = hasher.salt();
+        assert_eq!(next[30], 0x01);
+        assert_eq!(next[31], 0x00);
+        assert_eq!(
+            hasher.address_bytes(),
+            create2_address_bytes(&[1u8; 20], &next, &[2u8; 32])
+        );
+    }
+
+    #[test]
+    fn zero_byte_scorer_counts_leading_and_total() {
+        let mut addr = [0x11u8; 20];
+        addr[0] = 0;
+        addr[1] = 0;
+        addr[5] = 0;
+        addr[19] = 0;
+        assert_eq!(ZeroByteScorer::new(ZeroMetric::Leading).score(&addr), 2);
+        assert_eq!(ZeroByteScorer::new(ZeroMetric::Total).score(&addr), 4);
+        assert_eq!(
+            ZeroByteScorer::new(ZeroMetric::Leading).score(&[0u8; 20]),
+            20
+        );
+        // A leading zero *nibble* is not a zero byte.
+        let mut half = [0xffu8; 20];
+        half[0] = 0x0f;
+        assert_eq!(ZeroByteScorer::new(ZeroMetric::Leading).score(&half), 0);
+    }
+
+    #[test]
+    fn match_rule_patterns_combine_with_prefix() {
+        let addr = addr_from_hex("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed");
+        let rule = MatchRule::new(&one("5a"), NONE)
+            .unwrap()
+            .with_patterns(&["f33669", "0000"])
+            .unwrap();
+        assert!(rule.matches(&addr));
+
+        let rule = MatchRule::new(&one("5a"), NONE)
+            .unwrap()
+            .with_patterns(&["~4"])
+            .unwrap();
+        assert!(!rule.matches(&addr));
+
+        assert!(MatchRule::new(NONE, NONE)
+            .unwrap()
+            .with_patterns(&["^g"])
+            .is_err());
+    }
+
+    #[test]
+    fn match_probability_counts_alternatives_once() {
+        let p = |prefixes: &[&str], suffixes: &[&str]| {
+            MatchRule::new(prefixes, suffixes)
+                .unwrap()
+                .match_probability()
+        };
+        assert_eq!(p(&[], &[]), 1.0);
+        assert_eq!(p(&["dead"], &[]), 16f64.powi(-4));
+        assert_eq!(p(&["dead"], &["beef"]), 16f64.powi(-8));
+        // Disjoint alternatives add up; `de` already covers `dead`.
+        assert_eq!(p(&["00", "ff"], &[]), 2.0 / 256.0);
+        assert_eq!(p(&["de", "dead"], &[]), 1.0 / 256.0);
+        assert_eq!(p(&["dead", "de", "dead"], &["0"]), 1.0 / 4096.0);
+
+        // Each letter's checksum case halves the odds; digits are unaffected.
+        let cs = MatchRule::new_case_sensitive(&["De"], &["0A"]).unwrap();
+        assert_eq!(cs.match_probability(), 16f64.powi(-4) / 8.0);
+        let either = MatchRule::new_case_sensitive(&["A", "a"], &[]).unwrap();
+        assert_eq!(either.match_probability(), 1.0 / 16.0);
+    }
 }
@@ -0,0 +1,339 @@
+//! Compact pattern language for address matching.
+//!
+//! A pattern is an optional `^` (anchor at the start of the address), a body,
+//! and an optional `$` (anchor at the end). Without anchors the body may
+//! appear anywhere. The body is a sequence of:
+//!
+//! - a hex digit, matching that nibble (case matters only in case-sensitive
+//!   mode, where it is checked against the EIP-55 checksum),
+//! - `?`, matching any nibble,
+//! - `~N`, a run of `N` identical nibbles of any value,
+//! - `{N}` after a hex digit or `?`, repeating it `N` times in total.
+//!
+//! Examples: `^dead?beef`, `c0ffee$`, `~6` (any 6 identical nibbles
+//! anywhere), `^0{8}`.
+//!
+//! Patterns are compiled once into per-position nibble masks plus a short
+//! list of run constraints, so matching a candidate never allocates.
+
+use super::checksum_bits;
+
+/// Marker in [`Pattern::want`] for a position that accepts any nibble.
+const ANY: u8 = 0xff;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Anchor {
+    None,
+    Start,
+    End,
+    Both,
+}
+
+/// A compiled address pattern. See the module docs for the syntax.
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    anchor: Anchor,
+    /// Wanted nibble per position of the window, or [`ANY`].
+    want: Vec<u8>,
+    /// `(start, len)` runs of identical nibbles, relative to the window.
+    runs: Vec<(usize, usize)>,
+    /// Case constraints relative to the window; shifted by the match offset.
+    upper: u64,
+    lower: u64,
+}
+
+impl Pattern {
+    /// Compile `s`. With `case_sensitive`, letters must match the case of the
+    /// EIP-55 checksummed address.
+    pub fn parse(s: &str, case_sensitive: bool) -> Result<Self, String> {
+        let (body, start) = match s.strip_prefix('^') {
+            Some(rest) => (rest, true),
+            None => (s, false),
+        };
+        let (body, end) = match body.strip_suffix('$') {
+            Some(rest) => (rest, true),
+            None => (body, false),
+        };
+        let anchor = match (start, end) {
+            (false, false) => Anchor::None,
+            (true, false) => Anchor::Start,
+            (false, true) => Anchor::End,
+            (true, true) => Anchor::Both,
+        };
+
+        let mut want: Vec<u8> = Vec::new();
+        let mut runs = Vec::new();
+        let mut upper = 0u64;
+        let mut lower = 0u64;
+        // Whether the last item was a single position that `{N}` may repeat.
+        let mut repeatable = false;
+
+        let chars: Vec<char> = body.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            i += 1;
+            match c {
+                '?' => {
+                    want.push(ANY);
+                    repeatable = true;
+                }
+                '~' => {
+                    let n = take_number(&chars, &mut i, s)?;
+                    if n < 2 {
+                        return Err(format!("run length must be at least 2 in {s:?}"));
+                    }
+                    runs.push((want.len(), n));
+                    want.extend(std::iter::repeat_n(ANY, n.min(41)));
+                    repeatable = false;
+                }
+                '{' => {
+                    if !repeatable {
+                        return Err(format!("'{{' must follow a hex digit or '?' in {s:?}"));
+                    }
+                    let n = take_number(&chars, &mut i, s)?;
+                    if chars.get(i) != Some(&'}') {
+                        return Err(format!("unterminated '{{' in {s:?}"));
+                    }
+                    i += 1;
+                    if n == 0 {
+                        return Err(format!("repeat count must be at least 1 in {s:?}"));
+                    }
+                    let last = want.len() - 1;
+                    let nibble = want[last];
+                    let (is_upper, is_lower) = (upper >> last & 1, lower >> last & 1);
+                    for _ in 1..n.min(41) {
+                        let pos = want.len();
+                        want.push(nibble);
+                        if pos < 64 {
+                            upper |= is_upper << pos;
+                            lower |= is_lower << pos;
+                        }
+                    }
+                    repeatable = false;
+                }
+                _ => match c.to_digit(16) {
+                    Some(d) => {
+                        let pos = want.len();
+                        want.push(d as u8);
+                        if case_sensitive && pos < 64 {
+                            if c.is_ascii_uppercase() {
+                                upper |= 1 << pos;
+                            } else if c.is_ascii_lowercase() {
+                                lower |= 1 << pos;
+                            }
+                        }
+                        repeatable = true;
+                    }
+                    None => {
+                        return Err(format!(
+                            "unexpected character {c:?} at position {} in {s:?}",
+                            i - 1
+                        ))
+                    }
+                },
+            }
+            if want.len() > 40 {
+                return Err(format!("pattern {s:?} is longer than 40 nibbles"));
+            }
+        }
+
+        if want.is_empty() {
+            return Err(format!("empty pattern {s:?}"));
+        }
+        if anchor == Anchor::Both && want.len() != 40 {
+            return Err(format!(
+                "pattern {s:?} is anchored at both ends but covers {} of 40 nibbles",
+                want.len()
+            ));
+        }
+        Ok(Self {
+            anchor,
+            want,
+            runs,
+            upper,
+            lower,
+        })
+    }
+
+    /// Number of nibbles the pattern spans.
+    pub fn len(&self) -> usize {
+        self.want.len()
+    }
+
+    /// Always false: empty patterns are rejected by [`Pattern::parse`].
+    pub fn is_empty(&self) -> bool {
+        self.want.is_empty()
+    }
+
+    /// Approximate probability that a random address matches; see
+    /// [`super::MatchRule::match_probability`].
+    pub(super) fn match_probability(&self) -> f64 {
+        let mut window = 1.0f64;
+        for (pos, &want) in self.want.iter().enumerate() {
+            if want != ANY {
+                window /= 16.0;
+                if pos < 64 && (self.upper | self.lower) >> pos & 1 == 1 {
+                    window /= 2.0;
+                }
+            }
+        }
+        for &(_, len) in &self.runs {
+            window /= 16f64.powi(len as i32 - 1);
+        }
+        match self.anchor {
+            Anchor::None => {
+                let offsets = (40 - self.want.len() + 1) as f64;
+                // 1 - (1 - window)^offsets, without losing tiny probabilities.
+                -(offsets * (-window).ln_1p()).exp_m1()
+            }
+            _ => window,
+        }
+    }
+
+    /// Test the pattern against an address, given its 40 nibbles.
+    /// `upper_bits` caches the EIP-55 checksum across patterns/alternatives.
+    #[inline]
+    pub(super) fn matches(
+        &self,
+        address: &[u8; 20],
+        nibbles: &[u8; 40],
+        upper_bits: &mut Option<u64>,
+    ) -> bool {
+        let len = self.want.len();
+        let (first, last) = match self.anchor {
+            Anchor::Start | Anchor::Both => (0, 0),
+            Anchor::End => (40 - len, 40 - len),
+            Anchor::None => (0, 40 - len),
+        };
+        (first..=last).any(|offset| self.matches_at(address, nibbles, offset, upper_bits))
+    }
+
+    #[inline]
+    fn matches_at(
+        &self,
+        address: &[u8; 20],
+        nibbles: &[u8; 40],
+        offset: usize,
+        upper_bits: &mut Option<u64>,
+    ) -> bool {
+        let window = &nibbles[offset..offset + self.want.len()];
+        for (&want, &got) in self.want.iter().zip(window) {
+            if want != ANY && want != got {
+                return false;
+            }
+        }
+        for &(start, len) in &self.runs {
+            let first = window[start];
+            if window[start + 1..start + len].iter().any(|&n| n != first) {
+                return false;
+            }
+        }
+        if (self.upper | self.lower) == 0 {
+            return true;
+        }
+        let bits = checksum_bits(address, upper_bits);
+        let (upper, lower) = (self.upper << offset, self.lower << offset);
+        bits & upper == upper && bits & lower == 0
+    }
+}
+
+/// Parse a decimal count starting at `chars[*i]`, advancing `i`.
+fn take_number(chars: &[char], i: &mut usize, s: &str) -> Result<usize, String> {
+    let start = *i;
+    while *i < chars.len() && chars[*i].is_ascii_digit() {
+        *i += 1;
+    }
+    if start == *i {
+        return Err(format!("expected a number at position {start} in {s:?}"));
+    }
+    chars[start..*i]
+        .iter()
+        .collect::<String>()
+        .parse()
+        .map_err(|e| format!("invalid number in {s:?}: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::address_to_nibbles;
+
+    fn addr_from_hex(s: &str) -> [u8; 20] {
+        let mut out = [0u8; 20];
+        hex::decode_to_slice(s.strip_prefix("0x").unwrap_or(s).to_lowercase(), &mut out).unwrap();
+        out
+    }
+
+    fn check(pattern: &str, case_sensitive: bool, address: &str) -> bool {
+        let p = Pattern::parse(pattern, case_sensitive).unwrap();
+        let addr = addr_from_hex(address);
+        p.matches(&addr, &address_to_nibbles(&addr), &mut None)
+    }
+
+    const ADDR: &str = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+
+    #[test]
+    fn anchors_and_wildcards() {
+        assert!(check("^5aa?b6", false, ADDR));
+        assert!(!check("^aaeb", false, ADDR));
+        assert!(check("aaeb", false, ADDR));
+        assert!(check("be?ed$", false, ADDR));
+        assert!(!check("be?e$", false, ADDR));
+        assert!(check("^?{40}$", false, ADDR));
+        assert!(check("f33669", false, ADDR));
+        assert!(!check("f33670", false, ADDR));
+    }
+
+    #[test]
+    fn runs_of_identical_nibbles() {
+        let addr = "0x1234567777777890abcdef1234567890abcdef12";
+        assert!(check("~7", false, addr));
+        assert!(!check("~8", false, addr));
+        assert!(check("6~7", false, addr));
+        assert!(!check("^~2", false, addr));
+        assert!(check(
+            "^0{2}",
+            false,
+            "0x00aa000000000000000000000000000000000000"
+        ));
+        assert!(!check(
+            "^0{3}",
+            false,
+            "0x00aa000000000000000000000000000000000000"
+        ));
+    }
+
+    #[test]
+    fn case_sensitive_patterns_shift_with_offset() {
+        assert!(check("F3E94", true, ADDR));
+        assert!(!check("f3e94", true, ADDR));
+        assert!(check("f3e94", false, ADDR));
+        assert!(check("E{1}f1BeA", true, ADDR));
+    }
+
+    #[test]
+    fn rejects_malformed_patterns() {
+        for bad in [
+            "", "^", "$", "xyz", "~1", "~", "{3}", "a{", "a{0}", "a{2", "~3{2}", "^abc$",
+        ] {
+            assert!(Pattern::parse(bad, false).is_err(), "{bad:?} should fail");
+        }
+        assert!(Pattern::parse(&"a".repeat(41), false).is_err());
+        assert!(Pattern::parse("a{41}", false).is_err());
+    }
+
+    #[test]
+    fn match_probability_of_anchored_and_sliding_patterns() {
+        let p = |s: &str, cs: bool| Pattern::parse(s, cs).unwrap().match_probability();
+        assert_eq!(p("^dead", false), 16f64.powi(-4));
+        assert_eq!(p("^d?ad", false), 16f64.powi(-3));
+        assert_eq!(p("^~3", false), 16f64.powi(-2));
+        assert_eq!(p("^Dead", true), 16f64.powi(-4) / 16.0);
+        assert_eq!(p("^?{40}$", false), 1.0);
+        // 37 possible offsets for an unanchored 4-nibble pattern.
+        let sliding = p("dead", false);
+        assert!(sliding < 37.0 * 16f64.powi(-4));
+        assert!(sliding > 36.9 * 16f64.powi(-4));
+    }
+}
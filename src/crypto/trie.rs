@@ -0,0 +1,97 @@
+//! Nibble tries over a rule's prefix/suffix alternatives.
+//!
+//! Scanning alternatives one by one is fine for `--suffix 001,002,003` but
+//! not for a `--prefix-file` of every word that can be spelled in hex. A
+//! trie keyed on nibbles read inwards from the anchored end matches in at
+//! most one step per nibble, however many alternatives there are.
+
+use super::{nibble_at, Alt, Side};
+
+/// One side's alternatives as a nibble trie: prefixes are read left to
+/// right, suffixes right to left.
+#[derive(Debug, Clone)]
+pub(super) struct AltTrie {
+    side: Side,
+    /// `children[node][nibble]`, with 0 for "no child" (the root, node 0, is
+    /// never a child).
+    children: Vec<[u32; 16]>,
+    /// Alternatives spelled by the path to each node, as indices into the
+    /// list the trie was built from. Usually zero or one; more when the
+    /// same nibbles appear with different checksum case.
+    ends: Vec<Vec<u32>>,
+}
+
+impl AltTrie {
+    pub(super) fn new(alts: &[Alt], side: Side) -> Self {
+        let mut trie = Self {
+            side,
+            children: vec![[0; 16]],
+            ends: vec![Vec::new()],
+        };
+        for (index, alt) in alts.iter().enumerate() {
+            let mut node = 0;
+            let mut step = |node: usize, nibble: u8| {
+                let child = trie.children[node][usize::from(nibble)];
+                if child != 0 {
+                    return child as usize;
+                }
+                trie.children.push([0; 16]);
+                trie.ends.push(Vec::new());
+                let child = trie.children.len() - 1;
+                trie.children[node][usize::from(nibble)] =
+                    u32::try_from(child).expect("trie fits in u32 node indices");
+                child
+            };
+            match side {
+                Side::Prefix => {
+                    for &nibble in &alt.nibbles {
+                        node = step(node, nibble);
+                    }
+                }
+                Side::Suffix => {
+                    for &nibble in alt.nibbles.iter().rev() {
+                        node = step(node, nibble);
+                    }
+                }
+            }
+            trie.ends[node].push(index as u32);
+        }
+        trie
+    }
+
+    /// Whether `address` starts (or ends) with an alternative `index` for
+    /// which `accept(index)` holds; the caller checks case constraints there.
+    #[inline]
+    pub(super) fn matches(&self, address: &[u8; 20], accept: impl FnMut(usize) -> bool) -> bool {
+        self.matches_at(address, 0, accept)
+    }
+
+    /// Like [`AltTrie::matches`], with the alternatives anchored `offset`
+    /// nibbles in from this side's end of the address.
+    #[inline]
+    pub(super) fn matches_at(
+        &self,
+        address: &[u8; 20],
+        offset: usize,
+        mut accept: impl FnMut(usize) -> bool,
+    ) -> bool {
+        let mut node = 0;
+        for inset in offset..=40 {
+            if self.ends[node].iter().any(|&index| accept(index as usize)) {
+                return true;
+            }
+            if inset == 40 {
+                break;
+            }
+            let pos = match self.side {
+                Side::Prefix => inset,
+                Side::Suffix => 39 - inset,
+            };
+            match self.children[node][usize::from(nibble_at(address, pos))] {
+                0 => return false,
+                child => node = child as usize,
+            }
+        }
+        false
+    }
+}
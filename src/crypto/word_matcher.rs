@@ -0,0 +1,188 @@
+//! Early rejection on raw Keccak output.
+//!
+//! Most candidates fail on their first one or two nibbles, so building the
+//! 20-byte address before rejecting them is wasted work. [`WordMatcher`]
+//! tests the three [`AddressWords`] the permutation leaves behind. Each side
+//! first looks up the nibbles at its end of the address in a bitset of the
+//! values any alternative allows, which rejects most candidates with one
+//! load. A few alternatives are then each checked as `(mask, value)` pairs
+//! with a few ANDs and compares; large sets stop at the bitset and leave
+//! the rest to the rule's trie.
+
+use super::{AddressWords, Alt, Side};
+
+/// Sides with up to this many alternatives check each one exactly here.
+const MAX_MASKED: usize = 8;
+
+/// Width of the end-of-address index for small sets (a 256-bit table) and
+/// for large ones (a 128 KiB table, 16⁵ bits), capped by the shortest
+/// alternative.
+const SMALL_INDEX_NIBBLES: usize = 2;
+const LARGE_INDEX_NIBBLES: usize = 5;
+
+/// Compiled prefix/suffix test of a [`super::MatchRule`], built by the rule
+/// and borrowed through [`super::MatchRule::word_matcher`].
+///
+/// A candidate that passes may still fail the full
+/// [`super::MatchRule::matches`] (checksum case, patterns, or for large
+/// alternative sets anything past the first few nibbles), so hits must be
+/// confirmed there; a candidate that fails here never matches the rule.
+#[derive(Debug, Clone)]
+pub struct WordMatcher {
+    prefix: SideMatcher,
+    suffix: SideMatcher,
+}
+
+impl WordMatcher {
+    pub(super) fn new(prefix_alts: &[Alt], suffix_alts: &[Alt]) -> Self {
+        Self {
+            prefix: SideMatcher::new(prefix_alts, Side::Prefix),
+            suffix: SideMatcher::new(suffix_alts, Side::Suffix),
+        }
+    }
+
+    /// False if the address held in `words` cannot match the rule.
+    #[inline]
+    pub fn may_match(&self, words: &AddressWords) -> bool {
+        self.prefix.may_match(words) && self.suffix.may_match(words)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SideMatcher {
+    side: Side,
+    /// No alternatives, or one that is empty: everything passes.
+    unconstrained: bool,
+    /// Number of nibbles covered by `index`.
+    index_nibbles: usize,
+    /// One bit per value of the `index_nibbles` nibbles at this side's end
+    /// of the address, set if some alternative allows it.
+    index: Vec<u64>,
+    /// `(mask, value)` per alternative, or empty for large sets.
+    alts: Vec<(AddressWords, AddressWords)>,
+}
+
+impl SideMatcher {
+    fn new(alts: &[Alt], side: Side) -> Self {
+        let shortest = alts.iter().map(|a| a.nibbles.len()).min().unwrap_or(0);
+        let index_nibbles = shortest.min(if alts.len() > MAX_MASKED {
+            LARGE_INDEX_NIBBLES
+        } else {
+            SMALL_INDEX_NIBBLES
+        });
+        let mut index = vec![0u64; (1usize << (4 * index_nibbles)).div_ceil(64)];
+        for alt in alts {
+            let edge = match side {
+                Side::Prefix => &alt.nibbles[..index_nibbles],
+                Side::Suffix => &alt.nibbles[alt.nibbles.len() - index_nibbles..],
+            };
+            let value = edge
+                .iter()
+                .fold(0usize, |acc, &nibble| acc << 4 | usize::from(nibble));
+            index[value / 64] |= 1 << (value % 64);
+        }
+        Self {
+            side,
+            unconstrained: shortest == 0,
+            index_nibbles,
+            index,
+            alts: if alts.len() > MAX_MASKED {
+                Vec::new()
+            } else {
+                alts.iter().map(|alt| compile(&alt.nibbles, side)).collect()
+            },
+        }
+    }
+
+    #[inline]
+    fn may_match(&self, words: &AddressWords) -> bool {
+        if self.unconstrained {
+            return true;
+        }
+        // Address bytes 0..4 are the top half of word 0 and bytes 16..20
+        // the top half of word 2, both little-endian.
+        let bits = 4 * self.index_nibbles as u32;
+        let edge = match self.side {
+            Side::Prefix => ((words[0] >> 32) as u32).swap_bytes() >> (32 - bits),
+            Side::Suffix => ((words[2] >> 32) as u32).swap_bytes() & ((1 << bits) - 1),
+        } as usize;
+        if self.index[edge / 64] >> (edge % 64) & 1 == 0 {
+            return false;
+        }
+        self.alts.is_empty()
+            || self.alts.iter().any(|(mask, value)| {
+                ((words[0] & mask[0]) ^ value[0])
+                    | ((words[1] & mask[1]) ^ value[1])
+                    | ((words[2] & mask[2]) ^ value[2])
+                    == 0
+            })
+    }
+}
+
+/// `(mask, value)` selecting `nibbles` at the start or end of the address.
+fn compile(nibbles: &[u8], side: Side) -> (AddressWords, AddressWords) {
+    let mut mask = [0u64; 3];
+    let mut value = [0u64; 3];
+    let base = match side {
+        Side::Prefix => 0,
+        Side::Suffix => 40 - nibbles.len(),
+    };
+    for (i, &nibble) in nibbles.iter().enumerate() {
+        let pos = base + i;
+        // Address byte `b` is byte `b + 4` of the words; within a byte the
+        // high nibble comes first.
+        let byte = pos / 2 + 4;
+        let shift = 8 * (byte % 8) + if pos % 2 == 0 { 4 } else { 0 };
+        mask[byte / 8] |= 0xf << shift;
+        value[byte / 8] |= u64::from(nibble) << shift;
+    }
+    (mask, value)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crypto::{address_from_words, AddressWords, MatchRule};
+    use rand::RngCore;
+
+    #[test]
+    fn agrees_with_byte_matching() {
+        let rules = [
+            MatchRule::new(&["dead"], &[] as &[&str]).unwrap(),
+            MatchRule::new(&["a", "b1", "c2d"], &["0", "ef"]).unwrap(),
+            MatchRule::new(&[] as &[&str], &["beef"]).unwrap(),
+            MatchRule::new(&["0123456789"], &["abcdef0123"]).unwrap(),
+        ];
+        let mut words: AddressWords = [0; 3];
+        for _ in 0..200_000 {
+            for word in &mut words {
+                *word = rand::thread_rng().next_u64();
+            }
+            let address = address_from_words(&words);
+            for rule in &rules {
+                assert_eq!(
+                    rule.word_matcher().may_match(&words),
+                    rule.matches(&address),
+                    "{address:02x?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn prefilter_only_rejects() {
+        // Case and patterns are left to `MatchRule::matches`, so anything
+        // matching on nibbles passes the word test.
+        let rule = MatchRule::new_case_sensitive(&["DeAd"], &[] as &[&str])
+            .unwrap()
+            .with_patterns(&["~4"])
+            .unwrap();
+        let mut address = [0u8; 20];
+        address[..2].copy_from_slice(&[0xde, 0xad]);
+        let mut words: AddressWords = [0; 3];
+        words[0] = u64::from_le_bytes([0, 0, 0, 0, 0xde, 0xad, 0, 0]);
+        assert_eq!(address_from_words(&words), address);
+        assert!(rule.word_matcher().may_match(&words));
+        words[0] ^= 1 << 36;
+        assert!(!rule.word_matcher().may_match(&words));
+    }
+}
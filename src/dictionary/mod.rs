@@ -0,0 +1,249 @@
+//! Hex-word ("leetspeak") vanity dictionaries.
+//!
+//! Only `a`-`f` are hex letters, but many more words can be spelled with
+//! look-alike digits: `0` for `o`, `1` for `i`/`l`, `5` for `s`, `7` for
+//! `t`, and so on. A [`Dictionary`] turns a word list into every hex
+//! spelling of each word, which the search then matches as prefix, suffix or
+//! infix alternatives, and maps a found address back to the word it spells.
+
+use std::collections::BTreeMap;
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+/// Hex digits that can stand in for each letter. `a`-`f` only stand for
+/// themselves so the common spellings stay readable.
+fn letter_spellings(c: char) -> &'static [char] {
+    match c {
+        'a' => &['a'],
+        'b' => &['b'],
+        'c' => &['c'],
+        'd' => &['d'],
+        'e' => &['e'],
+        'f' => &['f'],
+        'g' => &['6', '9'],
+        'i' | 'l' => &['1'],
+        'o' => &['0'],
+        's' => &['5'],
+        't' => &['7'],
+        'z' => &['2'],
+        _ => &[],
+    }
+}
+
+/// Most spellings kept for one word. Ambiguous letters multiply the
+/// spellings (a word with k `g`s has 2^k), so words over this are skipped.
+pub const MAX_SPELLINGS_PER_WORD: usize = 256;
+
+/// Hex digits that can stand in for `c`, already lowercase.
+fn char_spellings(c: char) -> &'static [char] {
+    const DIGITS: [char; 10] = ['0', '1', '2', '3', '4', '5', '6', '7', '8', '9'];
+    match c.to_digit(10) {
+        Some(digit) => &DIGITS[digit as usize..digit as usize + 1],
+        None => letter_spellings(c),
+    }
+}
+
+/// Number of hex spellings of `word`, saturating at `usize::MAX`.
+fn spelling_count(word: &str) -> usize {
+    word.chars()
+        .map(|c| char_spellings(c.to_ascii_lowercase()).len())
+        .fold(1, usize::saturating_mul)
+}
+
+/// Every hex spelling of `word` (case-insensitive), or none if some letter
+/// has no hex look-alike or there are more than [`MAX_SPELLINGS_PER_WORD`].
+/// Digits spell themselves.
+pub fn hex_spellings(word: &str) -> Vec<String> {
+    if spelling_count(word) > MAX_SPELLINGS_PER_WORD {
+        return Vec::new();
+    }
+    let mut spellings = vec![String::new()];
+    for c in word.chars().map(|c| c.to_ascii_lowercase()) {
+        let options = char_spellings(c);
+        if options.is_empty() {
+            return Vec::new();
+        }
+        spellings = spellings
+            .iter()
+            .flat_map(|prefix| options.iter().map(move |&o| format!("{prefix}{o}")))
+            .collect();
+    }
+    spellings
+}
+
+/// Where in the address a word must appear.
+#[derive(clap::ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum WordPosition {
+    Start,
+    End,
+    Anywhere,
+}
+
+impl WordPosition {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WordPosition::Start => "start",
+            WordPosition::End => "end",
+            WordPosition::Anywhere => "anywhere",
+        }
+    }
+}
+
+/// The hex spellings of a word list, each mapped back to its word.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Dictionary {
+    pub position: WordPosition,
+    /// Spelling -> word. When two words share a spelling the first is kept.
+    spellings: BTreeMap<String, String>,
+    /// Words skipped for having more than [`MAX_SPELLINGS_PER_WORD`]
+    /// spellings.
+    #[serde(skip)]
+    too_ambiguous: Vec<String>,
+}
+
+impl Dictionary {
+    /// Keep the words of at least `min_length` characters (and at most 40)
+    /// that can be spelled in hex, in at most [`MAX_SPELLINGS_PER_WORD`]
+    /// ways.
+    pub fn new<S: AsRef<str>>(words: &[S], min_length: usize, position: WordPosition) -> Self {
+        let mut spellings = BTreeMap::new();
+        let mut too_ambiguous = Vec::new();
+        for word in words {
+            let word = word.as_ref().trim().to_ascii_lowercase();
+            let len = word.chars().count();
+            if len < min_length.max(1) || len > 40 {
+                continue;
+            }
+            if spelling_count(&word) > MAX_SPELLINGS_PER_WORD {
+                too_ambiguous.push(word);
+                continue;
+            }
+            for spelling in hex_spellings(&word) {
+                spellings.entry(spelling).or_insert_with(|| word.clone());
+            }
+        }
+        Self {
+            position,
+            spellings,
+            too_ambiguous,
+        }
+    }
+
+    /// Read a word list, one word per line; blank lines and lines starting
+    /// with `#` are skipped. Errors if no word can be spelled in hex.
+    pub fn from_file(
+        path: &str,
+        min_length: usize,
+        position: WordPosition,
+    ) -> Result<Self, String> {
+        let content =
+            fs::read_to_string(path).map_err(|e| format!("Failed to read {path}: {e}"))?;
+        let words: Vec<&str> = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .collect();
+        let dictionary = Self::new(&words, min_length, position);
+        if dictionary.is_empty() {
+            return Err(format!(
+                "{path} has no words of {min_length}+ letters that can be spelled in hex"
+            ));
+        }
+        Ok(dictionary)
+    }
+
+    /// Words left out for having more than [`MAX_SPELLINGS_PER_WORD`]
+    /// spellings, so the caller can warn about them.
+    pub fn too_ambiguous(&self) -> &[String] {
+        &self.too_ambiguous
+    }
+
+    /// Number of hex spellings.
+    pub fn len(&self) -> usize {
+        self.spellings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spellings.is_empty()
+    }
+
+    /// Number of distinct words with at least one spelling.
+    pub fn word_count(&self) -> usize {
+        let mut words: Vec<&String> = self.spellings.values().collect();
+        words.sort();
+        words.dedup();
+        words.len()
+    }
+
+    /// All spellings, to use as match alternatives.
+    pub fn spellings(&self) -> Vec<String> {
+        self.spellings.keys().cloned().collect()
+    }
+
+    /// The longest word spelled at [`Dictionary::position`] in `address`
+    /// (hex, with or without `0x`, any case), if any.
+    pub fn word_for(&self, address: &str) -> Option<&str> {
+        let hex = address
+            .strip_prefix("0x")
+            .unwrap_or(address)
+            .to_ascii_lowercase();
+        self.spellings
+            .iter()
+            .filter(|(spelling, _)| match self.position {
+                WordPosition::Start => hex.starts_with(spelling.as_str()),
+                WordPosition::End => hex.ends_with(spelling.as_str()),
+                WordPosition::Anywhere => hex.contains(spelling.as_str()),
+            })
+            .max_by_key(|(spelling, _)| spelling.len())
+            .map(|(_, word)| word.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn words_translate_to_every_hex_spelling() {
+        assert_eq!(hex_spellings("beef"), ["beef"]);
+        assert_eq!(hex_spellings("Coffee"), ["c0ffee"]);
+        assert_eq!(hex_spellings("toilets"), ["7011e75"]);
+        assert_eq!(hex_spellings("egg"), ["e66", "e69", "e96", "e99"]);
+        assert_eq!(hex_spellings("b00st"), ["b0057"]);
+        assert!(hex_spellings("hello").is_empty());
+        assert!(hex_spellings("don't").is_empty());
+    }
+
+    #[test]
+    fn dictionary_filters_and_maps_spellings_back() {
+        let words = ["bad", "cafe", "decaf", "hex", "sofa", "ace", "coffee"];
+        let dict = Dictionary::new(&words, 4, WordPosition::Start);
+        assert_eq!(dict.spellings(), ["50fa", "c0ffee", "cafe", "decaf"]);
+        assert_eq!(dict.word_count(), 4);
+        assert_eq!(dict.word_for("0xC0FFEE00"), Some("coffee"));
+        assert_eq!(dict.word_for("00c0ffee"), None);
+
+        let anywhere = Dictionary::new(&words, 3, WordPosition::Anywhere);
+        // Both `ace` and `cafe` appear; the longer word wins.
+        assert_eq!(anywhere.word_for("0xacecafe"), Some("cafe"));
+        assert_eq!(anywhere.word_for("0x0000ace0"), Some("ace"));
+        let end = Dictionary::new(&words, 3, WordPosition::End);
+        assert_eq!(end.word_for("0x1234bad"), Some("bad"));
+    }
+
+    #[test]
+    fn words_with_too_many_spellings_are_skipped() {
+        // 8 `g`s: 256 spellings, right at the cap.
+        assert_eq!(hex_spellings("gggggggg").len(), MAX_SPELLINGS_PER_WORD);
+        // 40 `g`s would be 2^40 spellings.
+        let long = "g".repeat(40);
+        assert!(hex_spellings(&long).is_empty());
+        assert!(hex_spellings(&"g".repeat(9)).is_empty());
+
+        let dict = Dictionary::new(&["egg", long.as_str(), "ggggggggg"], 3, WordPosition::Start);
+        assert_eq!(dict.len(), 4);
+        assert_eq!(dict.too_ambiguous(), [long.as_str(), "ggggggggg"]);
+    }
+}
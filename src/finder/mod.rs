@@ -1,14 +1,25 @@
 use num_cpus;
 use rayon::prelude::*;
-use rayon::ThreadPoolBuilder;
-use std::io::{self, Write};
-use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
-use std::thread;
+use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
 
-use crate::crypto::{address_to_hex, IncrementalKeygen, MatchRule};
-use crate::models::FoundAddress;
+use bip39::Mnemonic;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use secp256k1::{PublicKey, SecretKey};
+
+use crate::crypto::{
+    address_from_words, address_to_checksum_hex, address_to_hex, address_words_from_uncompressed,
+    check_mnemonic_word_count, contract_address_bytes, eth_account_parent, generate_mnemonic,
+    generate_private_key, seeded_private_key, BatchKeygen, Create2Hasher, MatchRule,
+    ZeroByteScorer, ETH_ACCOUNT_PATH, KEYGEN_BATCH_SIZE,
+};
+use crate::models::{BenchSample, FoundAddress, FoundMnemonic, FoundSalt, ScoredAddress};
+use crate::stats::{format_estimate, time_for, Difficulty, CONFIDENCE_LEVELS};
+use crate::{say, say_partial};
 
 /// Format duration as hours:minutes:seconds, omitting empty parts
 fn format_duration(duration: Duration) -> String {
@@ -26,143 +37,1138 @@ fn format_duration(duration: Duration) -> String {
     }
 }
 
-/// Find addresses in parallel
-pub fn find_addresses_parallel(
-    count: usize,
-    prefix: &Option<Vec<String>>,
-    suffix: &Option<Vec<String>>,
-    threads: usize,
-) -> Vec<FoundAddress> {
-    let thread_count = if threads > 0 {
+fn resolve_threads(threads: usize) -> usize {
+    if threads > 0 {
         threads
     } else {
         num_cpus::get()
-    };
+    }
+}
 
-    // Use a local pool so callers can invoke this more than once per process
-    // (e.g. tests, benchmarks, or library consumers). `build_global` is a
-    // one-shot and would panic on the second call.
-    let pool = ThreadPoolBuilder::new()
+/// Use a local pool so callers can invoke the search more than once per
+/// process (e.g. tests, benchmarks, or library consumers). `build_global` is
+/// a one-shot and would panic on the second call.
+fn build_pool(thread_count: usize) -> ThreadPool {
+    ThreadPoolBuilder::new()
         .num_threads(thread_count)
         .build()
-        .expect("failed to build rayon thread pool");
+        .expect("failed to build rayon thread pool")
+}
 
-    let found_addresses = Arc::new(Mutex::new(Vec::with_capacity(count)));
-    let found_count = Arc::new(AtomicUsize::new(0));
-    let total_checked = Arc::new(AtomicU64::new(0));
+/// Where `worker` starts its `epoch`-th walk: derived from `seed` if the
+/// search has one, fresh entropy otherwise.
+fn start_key(seed: Option<u64>, worker: usize, epoch: u64) -> SecretKey {
+    match seed {
+        Some(seed) => seeded_private_key(seed, worker as u64, epoch),
+        None => generate_private_key(),
+    }
+}
 
-    let start_time = Instant::now();
+fn format_address(bytes: &[u8; 20], case_sensitive: bool) -> String {
+    if case_sensitive {
+        address_to_checksum_hex(bytes)
+    } else {
+        address_to_hex(bytes)
+    }
+}
 
-    println!("Using {thread_count} CPU threads for parallel processing");
+/// Addresses per second for `checked` candidates in `elapsed`.
+fn rate(checked: u64, elapsed: Duration) -> f64 {
+    if elapsed.as_secs() > 0 {
+        checked as f64 / elapsed.as_secs_f64()
+    } else {
+        0.0
+    }
+}
 
-    let progress_found_count = Arc::clone(&found_count);
-    let progress_total_checked = Arc::clone(&total_checked);
-    let progress_start_time = start_time;
-    let progress_handle = thread::spawn(move || loop {
-        thread::sleep(Duration::from_millis(500));
-        let found = progress_found_count.load(Ordering::Relaxed);
-        if found >= count {
+/// On a thread of `scope`, call `tick` with the time elapsed in this run
+/// every [`PROGRESS_INTERVAL`] until `done` returns true. Once `time_budget`
+/// has passed, set `timed_out` and stop instead.
+fn spawn_progress<'scope>(
+    scope: &'scope thread::Scope<'scope, '_>,
+    start_time: Instant,
+    time_budget: Option<Duration>,
+    timed_out: &'scope AtomicBool,
+    done: impl Fn() -> bool + Send + 'scope,
+    tick: impl Fn(Duration) + Send + 'scope,
+) {
+    scope.spawn(move || loop {
+        thread::sleep(PROGRESS_INTERVAL);
+        if done() {
+            break;
+        }
+        let elapsed = start_time.elapsed();
+        if time_budget.is_some_and(|budget| elapsed >= budget) {
+            timed_out.store(true, Ordering::Relaxed);
             break;
         }
-        let checked = progress_total_checked.load(Ordering::Relaxed);
-        let elapsed = progress_start_time.elapsed();
-        let speed = if elapsed.as_secs() > 0 {
-            checked as f64 / elapsed.as_secs_f64()
+        tick(elapsed);
+    });
+}
+
+/// Clear the progress line and print `headline` with the totals.
+fn print_summary(headline: &str, checked: u64, elapsed: Duration) {
+    say_partial!("\r");
+    say!(
+        "{headline}, {checked} total checked, {:.0} addr/sec average, {}",
+        rate(checked, elapsed),
+        format_duration(elapsed)
+    );
+}
+
+/// Set `cancel` when the process receives Ctrl-C (SIGINT) or SIGTERM, so the
+/// search loops stop and the caller can save what was found. A second signal
+/// exits immediately. Can only be installed once per process.
+pub fn cancel_on_interrupt(cancel: Arc<AtomicBool>) -> Result<(), String> {
+    ctrlc::set_handler(move || {
+        if cancel.swap(true, Ordering::Relaxed) {
+            eprintln!("\nInterrupted again, exiting without saving");
+            std::process::exit(130);
+        }
+        eprintln!(
+            "\nInterrupted, stopping workers and saving results (Ctrl-C again to force quit)"
+        );
+    })
+    .map_err(|e| format!("Error installing signal handler: {e}"))
+}
+
+/// Which address derived from each candidate key is tested against the rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchMode {
+    /// The externally-owned account address of the key itself.
+    #[default]
+    Eoa,
+    /// The address of the contract the key would deploy with `CREATE` at
+    /// the given account nonce (0 for a fresh EOA's first deployment).
+    Create { nonce: u64 },
+}
+
+impl SearchMode {
+    /// The address tested against the rule for a key with `address`.
+    fn target(self, address: [u8; 20]) -> [u8; 20] {
+        match self {
+            SearchMode::Eoa => address,
+            SearchMode::Create { nonce } => contract_address_bytes(&address, nonce),
+        }
+    }
+}
+
+/// Counters for a search, cumulative across resumed runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SearchProgress {
+    pub found: usize,
+    pub checked: u64,
+    pub elapsed: Duration,
+}
+
+/// How a [`Search`] ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchStatus {
+    Completed,
+    /// Stopped by the cancellation flag with `found` of `requested` matches
+    /// (cumulative across resumed runs).
+    Cancelled {
+        found: usize,
+        requested: usize,
+    },
+    /// Stopped by [`Search::time_budget`] with `found` of `requested` matches.
+    TimedOut {
+        found: usize,
+        requested: usize,
+    },
+}
+
+/// Result of a [`Search`]: the addresses found in this run and how the
+/// search ended.
+#[derive(Debug)]
+pub struct SearchOutcome {
+    pub status: SearchStatus,
+    pub addresses: Vec<FoundAddress>,
+    /// With [`Search::mnemonics`], the same matches with the mnemonic and
+    /// derivation path each key is recovered from.
+    pub mnemonics: Vec<FoundMnemonic>,
+    /// With [`Search::create2`], the salts found; `addresses` is then empty.
+    pub salts: Vec<FoundSalt>,
+}
+
+impl SearchOutcome {
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self.status, SearchStatus::Cancelled { .. })
+    }
+}
+
+/// How often the CLI checkpoints a running search.
+pub const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Re-seed each thread's key walk every `RESEED_AFTER` candidates so that a
+/// thread that explored a long dead-end region eventually jumps to a fresh
+/// random starting point. Without this, the search is biased toward the
+/// neighbourhood of the initial key and could get stuck in a bad area for
+/// hard targets. A batch of ~1M keys takes a few seconds at our throughput,
+/// so the amortized cost of the reseed scalar-mult is negligible.
+const RESEED_AFTER: u64 = 1_000_000;
+
+/// Salts a [`Search::create2`] worker checks between updates of the shared
+/// checked counter.
+const SALT_BATCH: u64 = 1024;
+
+/// How often [`SearchObserver::on_progress`] is called.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Receives events from a running [`Search`].
+///
+/// Hooks are called from the search's worker and progress threads, possibly
+/// concurrently, and must not block for long. Every hook defaults to a no-op.
+pub trait SearchObserver: Sync {
+    /// A match was found. `progress.found` is this match's 1-based index.
+    fn on_match(&self, _found: &FoundAddress, _progress: SearchProgress) {}
+
+    /// A match was found by a [`Search::mnemonics`] search. Defaults to
+    /// [`SearchObserver::on_match`] with the account's key.
+    fn on_mnemonic_match(&self, found: &FoundMnemonic, progress: SearchProgress) {
+        self.on_match(&found.found, progress);
+    }
+
+    /// A salt was found by a [`Search::create2`] search.
+    fn on_salt_match(&self, _found: &FoundSalt, _progress: SearchProgress) {}
+
+    /// Periodic snapshot; `rate` is addresses per second in this run.
+    fn on_progress(&self, _progress: SearchProgress, _rate: f64) {}
+
+    /// The search has stopped; called exactly once, after all matches.
+    fn on_finish(&self, _outcome: &SearchOutcome, _progress: SearchProgress) {}
+}
+
+/// The observer used by [`find_addresses_parallel`] and the CLI: prints each
+/// match, a progress line and the final statistics to stdout, or to stderr
+/// after [`messages_to_stderr`](crate::utils::messages_to_stderr).
+#[derive(Debug)]
+pub struct StdoutObserver {
+    requested: usize,
+    difficulty: Option<Difficulty>,
+    /// Attempts for the remaining matches (mean, then each of
+    /// [`CONFIDENCE_LEVELS`]), keyed by the remaining count.
+    estimates: Mutex<Option<(usize, [f64; 4])>>,
+}
+
+impl StdoutObserver {
+    /// `requested` is the total match count shown as "(n/requested)".
+    pub fn new(requested: usize) -> Self {
+        Self {
+            requested,
+            difficulty: None,
+            estimates: Mutex::new(None),
+        }
+    }
+
+    /// Extend the progress line with time estimates and a luck indicator.
+    pub fn with_difficulty(mut self, difficulty: Difficulty) -> Self {
+        self.difficulty = Some(difficulty);
+        self
+    }
+
+    /// ", ETA ... (50% ..., 90% ..., 99% ...), luck N%" for the progress line.
+    fn estimate(&self, difficulty: &Difficulty, progress: SearchProgress, rate: f64) -> String {
+        let remaining = self.requested.saturating_sub(progress.found);
+        let mut estimates = self.estimates.lock().unwrap();
+        let attempts = match *estimates {
+            Some((cached, attempts)) if cached == remaining => attempts,
+            _ => {
+                let [a, b, c] = CONFIDENCE_LEVELS.map(|q| difficulty.attempts_for(remaining, q));
+                let attempts = [difficulty.expected_attempts(remaining), a, b, c];
+                *estimates = Some((remaining, attempts));
+                attempts
+            }
+        };
+        let luck = difficulty.luck(progress.checked, progress.found);
+        if rate <= 0.0 {
+            return format!(", luck {:.0}%", luck * 100.0);
+        }
+        let eta = |attempts: f64| format_estimate(time_for(attempts, rate));
+        let quantiles: Vec<String> = CONFIDENCE_LEVELS
+            .iter()
+            .zip(&attempts[1..])
+            .map(|(q, &a)| format!("{:.0}% {}", q * 100.0, eta(a)))
+            .collect();
+        format!(
+            ", ETA {} ({}), luck {:.0}%",
+            eta(attempts[0]),
+            quantiles.join(", "),
+            luck * 100.0
+        )
+    }
+}
+
+impl SearchObserver for StdoutObserver {
+    fn on_match(&self, found: &FoundAddress, progress: SearchProgress) {
+        say_partial!("\r");
+        match &found.contract_address {
+            Some(contract) => say!(
+                "Found matching contract address: {} (deployer {}) ({}/{})",
+                contract,
+                found.address,
+                progress.found,
+                self.requested
+            ),
+            None => say!(
+                "Found matching address: {} ({}/{})",
+                found.address,
+                progress.found,
+                self.requested
+            ),
+        }
+    }
+
+    fn on_mnemonic_match(&self, found: &FoundMnemonic, progress: SearchProgress) {
+        say_partial!("\r");
+        say!(
+            "Found matching address: {} at {} ({}/{})",
+            found
+                .found
+                .contract_address
+                .as_deref()
+                .unwrap_or(&found.found.address),
+            found.derivation_path,
+            progress.found,
+            self.requested
+        );
+    }
+
+    fn on_salt_match(&self, found: &FoundSalt, progress: SearchProgress) {
+        say_partial!("\r");
+        say!(
+            "Found matching address: {} with salt 0x{} ({}/{})",
+            found.address,
+            hex::encode(found.salt),
+            progress.found,
+            self.requested
+        );
+    }
+
+    fn on_progress(&self, progress: SearchProgress, rate: f64) {
+        let time_str = format_duration(progress.elapsed);
+        let estimate = match &self.difficulty {
+            Some(difficulty) => self.estimate(difficulty, progress, rate),
+            None => String::new(),
+        };
+        say_partial!(
+            "\rProgress: {} found, {} checked, {rate:.0} addr/sec, {time_str}{estimate}",
+            progress.found,
+            progress.checked
+        );
+    }
+
+    fn on_finish(&self, outcome: &SearchOutcome, progress: SearchProgress) {
+        let headline = match outcome.status {
+            SearchStatus::Completed => {
+                format!("Search completed: {} addresses found", progress.found)
+            }
+            SearchStatus::Cancelled { found, requested } => {
+                format!("Search cancelled: {found} of {requested} addresses found")
+            }
+            SearchStatus::TimedOut { found, requested } => {
+                format!("Search timed out: {found} of {requested} addresses found")
+            }
+        };
+        print_summary(&headline, progress.checked, progress.elapsed);
+    }
+}
+
+/// How a [`Search::mnemonics`] search draws and walks its keys.
+#[derive(Debug, Clone, Copy)]
+pub struct MnemonicSearch {
+    word_count: usize,
+    indexes: u32,
+}
+
+impl MnemonicSearch {
+    /// Draw mnemonics of `word_count` words (12, 15, 18, 21 or 24) and check
+    /// accounts `m/44'/60'/0'/0/i` for `i < indexes`, which must be between 1
+    /// and 2^31 (the non-hardened indexes).
+    pub fn new(word_count: usize, indexes: u32) -> Result<Self, String> {
+        check_mnemonic_word_count(word_count)?;
+        if !(1..=1 << 31).contains(&indexes) {
+            return Err(format!(
+                "accounts per mnemonic must be between 1 and 2^31 (got {indexes})"
+            ));
+        }
+        Ok(Self {
+            word_count,
+            indexes,
+        })
+    }
+
+    /// Words per generated mnemonic.
+    pub fn word_count(&self) -> usize {
+        self.word_count
+    }
+
+    /// Accounts checked per mnemonic. With 1, every match is its wallet's
+    /// first account.
+    pub fn indexes(&self) -> u32 {
+        self.indexes
+    }
+}
+
+/// Builder for a vanity address search.
+///
+/// ```no_run
+/// use address_finder::{MatchRule, Search, StdoutObserver};
+///
+/// let rule = MatchRule::new(&["dead"], &[]).unwrap();
+/// let handle = Search::new(rule).count(2).threads(4).spawn(StdoutObserver::new(2));
+/// // ... later, from any thread:
+/// handle.cancel();
+/// let outcome = handle.join();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Search {
+    rule: MatchRule,
+    count: usize,
+    threads: usize,
+    mode: SearchMode,
+    time_budget: Option<Duration>,
+    prior: SearchProgress,
+    seed: Option<u64>,
+    split_key: Option<PublicKey>,
+    mnemonics: Option<MnemonicSearch>,
+    create2: Option<([u8; 20], [u8; 32])>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl Search {
+    /// A search for one address matching `rule`, using all CPU cores.
+    pub fn new(rule: MatchRule) -> Self {
+        Self {
+            rule,
+            count: 1,
+            threads: 0,
+            mode: SearchMode::Eoa,
+            time_budget: None,
+            prior: SearchProgress::default(),
+            seed: None,
+            split_key: None,
+            mnemonics: None,
+            create2: None,
+            cancel: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Stop once this many matches have been found (cumulative with
+    /// [`Search::resume_from`]).
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// Worker thread count; 0 means one per CPU core.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// Which derived address is matched against the rule.
+    pub fn mode(mut self, mode: SearchMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Give up after this much wall-clock time in this run.
+    pub fn time_budget(mut self, budget: Duration) -> Self {
+        self.time_budget = Some(budget);
+        self
+    }
+
+    /// Continue a job whose earlier runs reached `prior`: only
+    /// `count - prior.found` new addresses are searched for, and progress is
+    /// reported as cumulative totals.
+    pub fn resume_from(mut self, prior: SearchProgress) -> Self {
+        self.prior = prior;
+        self
+    }
+
+    /// Derive each worker's starting keys from `seed` instead of fresh
+    /// entropy. Every worker then walks the same keys on every run, so a
+    /// single-threaded search finds the same addresses each time (with more
+    /// threads, which worker gets there first can still vary).
+    ///
+    /// **Insecure**: anyone who knows the seed can recompute the keys; see
+    /// [`seeded_private_key`]. Only for tests and benchmarks.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Search for offsets `k` such that the address of `public + k·G`
+    /// matches, so the private key of `public` never has to be on this
+    /// machine. Each [`FoundAddress::private_key`] is then the partial key
+    /// `k`, which [`combine_split_key`](crate::crypto::combine_split_key)
+    /// turns into the real key.
+    pub fn split_key(mut self, public: PublicKey) -> Self {
+        self.split_key = Some(public);
+        self
+    }
+
+    /// Search keys that can be recovered from a BIP-39 mnemonic: each worker
+    /// draws a fresh mnemonic, checks the first [`MnemonicSearch::indexes`]
+    /// accounts under [`ETH_ACCOUNT_PATH`], then draws the next. Every
+    /// account costs a scalar multiplication and every mnemonic a
+    /// 2048-round PBKDF2, so this is far slower than the default walk.
+    /// Matches go to [`SearchObserver::on_mnemonic_match`]. Mnemonics always
+    /// use fresh entropy, so [`Search::seed`] and [`Search::split_key`] don't
+    /// apply.
+    pub fn mnemonics(mut self, mnemonics: MnemonicSearch) -> Self {
+        self.mnemonics = Some(mnemonics);
+        self
+    }
+
+    /// Mine `CREATE2` salts instead of keys: each worker walks its own run of
+    /// salts from a random starting point, testing
+    /// `keccak(0xff ++ deployer ++ salt ++ init_code_hash)[12..]` against the
+    /// rule. There is no secp256k1 work at all, so [`Search::mode`],
+    /// [`Search::seed`], [`Search::split_key`] and [`Search::mnemonics`] don't
+    /// apply. Matches go to [`SearchObserver::on_salt_match`].
+    pub fn create2(mut self, deployer: [u8; 20], init_code_hash: [u8; 32]) -> Self {
+        self.create2 = Some((deployer, init_code_hash));
+        self
+    }
+
+    /// Share an existing cancellation flag, e.g. one set by
+    /// [`cancel_on_interrupt`].
+    pub fn cancel_flag(mut self, cancel: Arc<AtomicBool>) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Number of worker threads the search will use.
+    pub fn thread_count(&self) -> usize {
+        resolve_threads(self.threads)
+    }
+
+    /// Run the search on a background thread.
+    pub fn spawn<O>(self, observer: O) -> SearchHandle
+    where
+        O: SearchObserver + Send + 'static,
+    {
+        let cancel = Arc::clone(&self.cancel);
+        let thread = thread::spawn(move || self.run(&observer));
+        SearchHandle { cancel, thread }
+    }
+
+    /// Run the search on the calling thread until it completes, is
+    /// cancelled, or runs out of time.
+    pub fn run<O: SearchObserver>(&self, observer: &O) -> SearchOutcome {
+        self.run_with(&self.cancel, observer)
+    }
+
+    fn run_with<O: SearchObserver>(&self, cancel: &AtomicBool, observer: &O) -> SearchOutcome {
+        let Self {
+            rule,
+            count,
+            mode,
+            time_budget,
+            prior,
+            seed,
+            split_key,
+            mnemonics,
+            create2,
+            ..
+        } = self;
+        let (count, mode, prior, seed, mnemonics, create2) =
+            (*count, *mode, *prior, *seed, *mnemonics, *create2);
+        let case_sensitive = rule.is_case_sensitive();
+        let thread_count = self.thread_count();
+        let pool = build_pool(thread_count);
+
+        // `count` may be effectively unbounded (e.g. benchmarks), so cap the
+        // up-front allocation.
+        let found_addresses = Mutex::new(Vec::with_capacity(
+            count.saturating_sub(prior.found).min(1024),
+        ));
+        let found_mnemonics = Mutex::new(Vec::new());
+        let found_salts = Mutex::new(Vec::new());
+        let found_count = AtomicUsize::new(prior.found);
+        let total_checked = AtomicU64::new(0);
+        let timed_out = AtomicBool::new(false);
+        let finished = AtomicBool::new(false);
+
+        let start_time = Instant::now();
+        let snapshot = || SearchProgress {
+            found: found_count.load(Ordering::Relaxed).min(count),
+            checked: prior.checked + total_checked.load(Ordering::Relaxed),
+            elapsed: prior.elapsed + start_time.elapsed(),
+        };
+        let stopped = || {
+            found_count.load(Ordering::Relaxed) >= count
+                || cancel.load(Ordering::Relaxed)
+                || timed_out.load(Ordering::Relaxed)
+        };
+
+        let words_match = rule.word_matcher();
+
+        // Reserve a slot for a match before doing any real work, so exactly
+        // `count` matches are reported. `None` once another thread has
+        // filled the last slot.
+        let reserve = || {
+            let slot = found_count.fetch_add(1, Ordering::Relaxed);
+            if slot >= count {
+                found_count.fetch_sub(1, Ordering::Relaxed);
+                return None;
+            }
+            Some(SearchProgress {
+                found: slot + 1,
+                ..snapshot()
+            })
+        };
+        // Hand a match to the observer. False once the last slot is taken.
+        let report = |private_key: SecretKey,
+                      address_bytes: &[u8; 20],
+                      target_bytes: &[u8; 20],
+                      account: Option<(&Mnemonic, u32)>| {
+            let Some(progress) = reserve() else {
+                return false;
+            };
+            let found = FoundAddress {
+                private_key,
+                address: format_address(address_bytes, case_sensitive),
+                contract_address: match mode {
+                    SearchMode::Eoa => None,
+                    SearchMode::Create { .. } => Some(format_address(target_bytes, case_sensitive)),
+                },
+            };
+            match account {
+                None => {
+                    observer.on_match(&found, progress);
+                    found_addresses.lock().unwrap().push(found);
+                }
+                Some((mnemonic, index)) => {
+                    let found = FoundMnemonic {
+                        found,
+                        mnemonic: mnemonic.to_string(),
+                        derivation_path: format!("{ETH_ACCOUNT_PATH}/{index}"),
+                    };
+                    observer.on_mnemonic_match(&found, progress);
+                    found_addresses.lock().unwrap().push(found.found.clone());
+                    found_mnemonics.lock().unwrap().push(found);
+                }
+            }
+            true
+        };
+        let report_salt = |salt: [u8; 32], address_bytes: &[u8; 20]| {
+            let Some(progress) = reserve() else {
+                return false;
+            };
+            let found = FoundSalt {
+                salt,
+                address: format_address(address_bytes, case_sensitive),
+            };
+            observer.on_salt_match(&found, progress);
+            found_salts.lock().unwrap().push(found);
+            true
+        };
+
+        thread::scope(|scope| {
+            spawn_progress(
+                scope,
+                start_time,
+                *time_budget,
+                &timed_out,
+                || finished.load(Ordering::Relaxed) || stopped(),
+                |run_elapsed| {
+                    let progress = snapshot();
+                    // Rate is for this run only; totals are cumulative.
+                    let rate = rate(progress.checked - prior.checked, run_elapsed);
+                    observer.on_progress(progress, rate);
+                },
+            );
+
+            pool.install(|| {
+                (0..thread_count).into_par_iter().for_each(|worker| {
+                    if let Some((deployer, init_code_hash)) = create2 {
+                        // A random 32-byte starting salt per thread keeps
+                        // threads (and separate runs) on disjoint ranges
+                        // without coordination.
+                        let mut salt = [0u8; 32];
+                        OsRng.fill_bytes(&mut salt);
+                        let mut hasher = Create2Hasher::new(&deployer, &salt, &init_code_hash);
+                        'salts: while !stopped() {
+                            // Count in batches: with no elliptic-curve work
+                            // the per-salt cost is a single Keccak, so a
+                            // per-candidate atomic add would show up as
+                            // contention across threads.
+                            let mut checked = 0;
+                            for _ in 0..SALT_BATCH {
+                                let address_bytes = hasher.address_bytes();
+                                checked += 1;
+                                if rule.matches(&address_bytes) {
+                                    total_checked.fetch_add(checked, Ordering::Relaxed);
+                                    checked = 0;
+                                    if !report_salt(hasher.salt(), &address_bytes) {
+                                        break 'salts;
+                                    }
+                                }
+                                hasher.advance();
+                            }
+                            total_checked.fetch_add(checked, Ordering::Relaxed);
+                        }
+                        return;
+                    }
+
+                    if let Some(mnemonics) = mnemonics {
+                        'mnemonics: while !stopped() {
+                            let mnemonic = generate_mnemonic(mnemonics.word_count)
+                                .expect("word count checked by MnemonicSearch::new");
+                            // Invalid only with negligible probability; draw again.
+                            let Ok(parent) = eth_account_parent(&mnemonic) else {
+                                continue;
+                            };
+                            for index in 0..mnemonics.indexes {
+                                if stopped() {
+                                    break 'mnemonics;
+                                }
+                                let Ok(account) = parent.child(index) else {
+                                    continue;
+                                };
+                                let address_bytes = account.address_bytes();
+                                let target_bytes = mode.target(address_bytes);
+                                total_checked.fetch_add(1, Ordering::Relaxed);
+                                if rule.matches(&target_bytes)
+                                    && !report(
+                                        account.secret(),
+                                        &address_bytes,
+                                        &target_bytes,
+                                        Some((&mnemonic, index)),
+                                    )
+                                {
+                                    break 'mnemonics;
+                                }
+                            }
+                        }
+                        return;
+                    }
+
+                    // Seeded epochs are numbered from the prior checked count,
+                    // which exceeds every epoch an earlier run reached, so a
+                    // resumed search never walks the same keys twice.
+                    let keygen = |epoch| {
+                        let start = start_key(seed, worker, epoch);
+                        match split_key {
+                            Some(public) => BatchKeygen::from_public_offset(public, start),
+                            None => BatchKeygen::from_secret(start),
+                        }
+                    };
+                    let mut epoch = prior.checked;
+                    let mut kg = keygen(epoch);
+                    let mut hashes = vec![[0u64; 3]; KEYGEN_BATCH_SIZE];
+                    let mut since_reseed: u64 = 0;
+                    'search: loop {
+                        if stopped() {
+                            break;
+                        }
+
+                        address_words_from_uncompressed(kg.next_batch(), &mut hashes);
+                        let mut checked = 0;
+                        for (index, words) in hashes.iter().enumerate() {
+                            checked += 1;
+                            let address_bytes = match mode {
+                                SearchMode::Eoa if !words_match.may_match(words) => continue,
+                                _ => address_from_words(words),
+                            };
+                            let target_bytes = mode.target(address_bytes);
+                            if !rule.matches(&target_bytes) {
+                                continue;
+                            }
+                            total_checked.fetch_add(checked, Ordering::Relaxed);
+                            checked = 0;
+                            if !report(kg.secret(index), &address_bytes, &target_bytes, None) {
+                                break 'search;
+                            }
+                        }
+                        total_checked.fetch_add(checked, Ordering::Relaxed);
+
+                        since_reseed += KEYGEN_BATCH_SIZE as u64;
+                        if since_reseed >= RESEED_AFTER {
+                            epoch += 1;
+                            kg = keygen(epoch);
+                            since_reseed = 0;
+                        }
+                    }
+                });
+            });
+            // Release the progress thread even if nothing tripped `stopped`
+            // (e.g. count was already reached by `prior`).
+            finished.store(true, Ordering::Relaxed);
+        });
+
+        let progress = snapshot();
+        let status = if progress.found >= count {
+            SearchStatus::Completed
+        } else if timed_out.load(Ordering::Relaxed) {
+            SearchStatus::TimedOut {
+                found: progress.found,
+                requested: count,
+            }
         } else {
-            0.0
+            SearchStatus::Cancelled {
+                found: progress.found,
+                requested: count,
+            }
         };
-        let time_str = format_duration(elapsed);
-        print!("\rProgress: {found} found, {checked} checked, {speed:.0} addr/sec, {time_str}");
-        io::stdout().flush().unwrap();
-    });
+        let outcome = SearchOutcome {
+            status,
+            addresses: found_addresses
+                .into_inner()
+                .expect("Mutex should not be poisoned"),
+            mnemonics: found_mnemonics
+                .into_inner()
+                .expect("Mutex should not be poisoned"),
+            salts: found_salts
+                .into_inner()
+                .expect("Mutex should not be poisoned"),
+        };
+        observer.on_finish(&outcome, progress);
+        outcome
+    }
+}
 
-    let prefixes: &[String] = prefix.as_deref().unwrap_or(&[]);
-    let suffixes: &[String] = suffix.as_deref().unwrap_or(&[]);
-    let rule = match MatchRule::new(prefixes, suffixes) {
-        Ok(r) => r,
-        Err(e) => {
-            eprintln!("Invalid prefix/suffix: {e}");
-            // Preserve prior behavior of returning an empty set on bad input
-            // rather than panicking; main() will save an empty result file.
-            return Vec::new();
+/// A [`Search`] running on a background thread.
+#[derive(Debug)]
+pub struct SearchHandle {
+    cancel: Arc<AtomicBool>,
+    thread: JoinHandle<SearchOutcome>,
+}
+
+impl SearchHandle {
+    /// Ask the workers to stop; [`SearchHandle::join`] then returns a
+    /// cancelled outcome with whatever was found so far.
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.thread.is_finished()
+    }
+
+    /// Wait for the search to stop and return its outcome.
+    pub fn join(self) -> SearchOutcome {
+        self.thread.join().expect("search thread panicked")
+    }
+}
+
+/// Find addresses in parallel
+///
+/// If `rule` is case-sensitive, found addresses are reported in EIP-55
+/// checksummed form. `mode` selects whether the EOA or its `CREATE` contract
+/// address is matched. Setting `cancel` (e.g. via [`cancel_on_interrupt`])
+/// stops the workers early; the outcome then reports how many were found.
+/// With a `seed`, starting keys are derived from it (see [`Search::seed`];
+/// insecure, for tests only). Matches and progress are printed to stdout;
+/// use [`Search`] to handle them yourself.
+pub fn find_addresses_parallel(
+    count: usize,
+    rule: &MatchRule,
+    threads: usize,
+    mode: SearchMode,
+    seed: Option<u64>,
+    cancel: &AtomicBool,
+) -> SearchOutcome {
+    let mut search = Search::new(rule.clone())
+        .count(count)
+        .threads(threads)
+        .mode(mode);
+    if let Some(seed) = seed {
+        search = search.seed(seed);
+    }
+    say!(
+        "Using {} CPU threads for parallel processing",
+        search.thread_count()
+    );
+    let observer = StdoutObserver::new(count).with_difficulty(Difficulty::from_rule(rule));
+    search.run_with(cancel, &observer)
+}
+
+/// Thread counts measured by [`benchmark_threads`] up to `max`: powers of
+/// two, plus `max` itself.
+pub fn bench_thread_counts(max: usize) -> Vec<usize> {
+    let max = resolve_threads(max);
+    let mut counts: Vec<usize> = std::iter::successors(Some(1usize), |n| n.checked_mul(2))
+        .take_while(|&n| n < max)
+        .collect();
+    counts.push(max);
+    counts
+}
+
+/// Measure the search hot loop with `rule` and `mode` for `duration` at each of
+/// `thread_counts`, in order. Each run is a real [`Search`] stopped by its
+/// time budget, so the numbers include key stepping, hashing and matching.
+/// `on_sample` is called as each measurement completes.
+pub fn benchmark_threads<F>(
+    rule: &MatchRule,
+    mode: SearchMode,
+    thread_counts: &[usize],
+    duration: Duration,
+    cancel: &AtomicBool,
+    on_sample: F,
+) -> Vec<BenchSample>
+where
+    F: Fn(&BenchSample),
+{
+    struct Counter(Mutex<SearchProgress>);
+
+    impl SearchObserver for Counter {
+        fn on_finish(&self, _outcome: &SearchOutcome, progress: SearchProgress) {
+            *self.0.lock().unwrap() = progress;
         }
+    }
+
+    let mut samples: Vec<BenchSample> = Vec::with_capacity(thread_counts.len());
+    for &threads in thread_counts {
+        if cancel.load(Ordering::Relaxed) {
+            break;
+        }
+        let counter = Counter(Mutex::new(SearchProgress::default()));
+        Search::new(rule.clone())
+            .count(usize::MAX)
+            .threads(threads)
+            .mode(mode)
+            .time_budget(duration)
+            .run_with(cancel, &counter);
+        let progress = counter.0.into_inner().unwrap();
+
+        let elapsed_secs = progress.elapsed.as_secs_f64();
+        let keys_per_sec = progress.checked as f64 / elapsed_secs;
+        let keys_per_sec_per_thread = keys_per_sec / threads as f64;
+        let baseline = samples
+            .first()
+            .map_or(keys_per_sec_per_thread, |s| s.keys_per_sec_per_thread);
+        let sample = BenchSample {
+            threads,
+            checked: progress.checked,
+            elapsed_secs,
+            keys_per_sec,
+            keys_per_sec_per_thread,
+            efficiency: keys_per_sec_per_thread / baseline,
+        };
+        on_sample(&sample);
+        samples.push(sample);
+    }
+    samples
+}
+
+/// When [`find_best_address_parallel`] gives up looking for a better score.
+/// With neither field set it runs until the process is interrupted, which is
+/// safe because every improvement has already been handed to the caller.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScoreStop {
+    /// Stop as soon as a candidate reaches this score.
+    pub target: Option<u32>,
+    /// Stop after this much wall-clock time, keeping the best so far.
+    pub time_budget: Option<Duration>,
+}
+
+/// How a [`find_best_address_parallel`] search ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoreStatus {
+    /// A candidate reached [`ScoreStop::target`].
+    TargetReached,
+    /// [`ScoreStop::time_budget`] ran out.
+    TimedOut,
+    /// Stopped by the cancellation flag.
+    Cancelled,
+}
+
+/// Result of [`find_best_address_parallel`]: the best candidate seen, if
+/// any scored, and how the search ended.
+#[derive(Debug)]
+pub struct ScoreOutcome {
+    pub status: ScoreStatus,
+    pub best: Option<ScoredAddress>,
+}
+
+/// Search for the highest-scoring address in parallel.
+///
+/// Unlike [`find_addresses_parallel`] there is no fixed match count: every
+/// candidate is scored with `scorer`, and each time any thread beats the
+/// best-so-far, `on_improvement` is called with the new best (serialized, in
+/// strictly increasing score order) so the caller can persist it right away.
+/// Only scores of at least 1 count, so an address without a single zero
+/// byte is never reported. Setting `cancel` stops the search like an expired
+/// time budget, and a `seed` makes the starting keys reproducible as in
+/// [`Search::seed`]. Keys are walked and hashed in batches like
+/// [`Search`]'s.
+pub fn find_best_address_parallel<F>(
+    scorer: ZeroByteScorer,
+    threads: usize,
+    mode: SearchMode,
+    stop: ScoreStop,
+    seed: Option<u64>,
+    cancel: &AtomicBool,
+    on_improvement: F,
+) -> ScoreOutcome
+where
+    F: Fn(&ScoredAddress) + Sync,
+{
+    let thread_count = resolve_threads(threads);
+    let pool = build_pool(thread_count);
+
+    // `best_score` is a lock-free pre-filter for the hot loop; `best` is the
+    // authority and is only touched on a (rare) apparent improvement. Both
+    // start at a score of 0, which is never an improvement. `reported` is the
+    // score last handed to `on_improvement`, so reports stay in order without
+    // holding `best` while the caller saves.
+    let best_score = AtomicU32::new(0);
+    let best: Mutex<Option<ScoredAddress>> = Mutex::new(None);
+    let reported = Mutex::new(0u32);
+    let reached = AtomicBool::new(false);
+    let timed_out = AtomicBool::new(false);
+    let total_checked = AtomicU64::new(0);
+
+    let start_time = Instant::now();
+    let stopped = || {
+        reached.load(Ordering::Relaxed)
+            || cancel.load(Ordering::Relaxed)
+            || timed_out.load(Ordering::Relaxed)
     };
 
-    // Re-seed each thread's incremental iterator every RESEED_AFTER candidates
-    // so that a thread that explored a long dead-end region eventually jumps
-    // to a fresh random starting point. Without this, the search is biased
-    // toward the neighbourhood of the initial key and could get stuck in a
-    // bad area for hard targets. A batch of ~1M keys takes a few seconds at
-    // our throughput, so the amortized cost of the reseed scalar-mult is
-    // negligible.
-    const RESEED_AFTER: u64 = 1_000_000;
-
-    pool.install(|| {
-        (0..thread_count).into_par_iter().for_each(|_| {
-            let mut kg = IncrementalKeygen::new();
-            let mut since_reseed: u64 = 0;
-            loop {
-                if found_count.load(Ordering::Relaxed) >= count {
-                    break;
+    say!("Using {thread_count} CPU threads for parallel processing");
+
+    let improve =
+        |private_key: SecretKey, address_bytes: &[u8; 20], target_bytes: &[u8; 20], score: u32| {
+            let scored = ScoredAddress {
+                found: FoundAddress {
+                    private_key,
+                    address: address_to_hex(address_bytes),
+                    contract_address: match mode {
+                        SearchMode::Eoa => None,
+                        SearchMode::Create { .. } => Some(address_to_hex(target_bytes)),
+                    },
+                },
+                score,
+            };
+            let improved = {
+                let mut best = best.lock().unwrap();
+                let improves = score > best.as_ref().map_or(0, |b| b.score);
+                if improves {
+                    best_score.store(score, Ordering::Relaxed);
+                    *best = Some(scored.clone());
                 }
+                improves
+            };
+            if !improved {
+                return;
+            }
+            if stop.target.is_some_and(|target| score >= target) {
+                reached.store(true, Ordering::Relaxed);
+            }
+            // A thread that improved further while this one waited has already
+            // reported, making this one stale.
+            let mut reported = reported.lock().unwrap();
+            if score > *reported {
+                *reported = score;
+                say_partial!("\r");
+                say!(
+                    "New best: {} with {} zero bytes",
+                    scored
+                        .found
+                        .contract_address
+                        .as_deref()
+                        .unwrap_or(&scored.found.address),
+                    score
+                );
+                on_improvement(&scored);
+            }
+        };
 
-                let address_bytes = kg.address_bytes();
-                total_checked.fetch_add(1, Ordering::Relaxed);
-
-                if rule.matches(&address_bytes) {
-                    // Reserve a slot before doing any real work so the final
-                    // length of `found_addresses` is exactly `count`.
-                    let slot = found_count.fetch_add(1, Ordering::Relaxed);
-                    if slot >= count {
-                        // Another thread already filled the last slot.
-                        found_count.fetch_sub(1, Ordering::Relaxed);
-                        break;
+    thread::scope(|scope| {
+        spawn_progress(
+            scope,
+            start_time,
+            stop.time_budget,
+            &timed_out,
+            stopped,
+            |elapsed| {
+                let best = best_score.load(Ordering::Relaxed);
+                let checked = total_checked.load(Ordering::Relaxed);
+                say_partial!(
+                    "\rProgress: best {best} zero bytes, {checked} checked, {:.0} addr/sec, {}",
+                    rate(checked, elapsed),
+                    format_duration(elapsed)
+                );
+            },
+        );
+
+        pool.install(|| {
+            (0..thread_count).into_par_iter().for_each(|worker| {
+                let mut epoch = 0;
+                let mut kg = BatchKeygen::from_secret(start_key(seed, worker, epoch));
+                let mut hashes = vec![[0u64; 3]; KEYGEN_BATCH_SIZE];
+                let mut since_reseed: u64 = 0;
+                while !stopped() {
+                    address_words_from_uncompressed(kg.next_batch(), &mut hashes);
+                    for (index, words) in hashes.iter().enumerate() {
+                        let address_bytes = address_from_words(words);
+                        let target_bytes = mode.target(address_bytes);
+                        let score = scorer.score(&target_bytes);
+                        if score > best_score.load(Ordering::Relaxed) {
+                            improve(kg.secret(index), &address_bytes, &target_bytes, score);
+                        }
                     }
-                    let private_key = kg.secret();
-                    let address = address_to_hex(&address_bytes);
-                    let mut found_vec = found_addresses.lock().unwrap();
-                    found_vec.push(FoundAddress {
-                        private_key,
-                        address: address.clone(),
-                    });
-                    print!("\r");
-                    println!(
-                        "Found matching address: {} ({}/{})",
-                        address,
-                        slot + 1,
-                        count
-                    );
-                }
+                    total_checked.fetch_add(KEYGEN_BATCH_SIZE as u64, Ordering::Relaxed);
 
-                kg.advance();
-                since_reseed += 1;
-                if since_reseed >= RESEED_AFTER {
-                    kg = IncrementalKeygen::new();
-                    since_reseed = 0;
+                    since_reseed += KEYGEN_BATCH_SIZE as u64;
+                    if since_reseed >= RESEED_AFTER {
+                        epoch += 1;
+                        kg = BatchKeygen::from_secret(start_key(seed, worker, epoch));
+                        since_reseed = 0;
+                    }
                 }
-            }
+            });
         });
     });
 
-    progress_handle.join().unwrap();
-
-    print!("\r");
-    let final_checked = total_checked.load(Ordering::Relaxed);
-    let elapsed = start_time.elapsed();
-    let final_speed = if elapsed.as_secs() > 0 {
-        final_checked as f64 / elapsed.as_secs_f64()
+    let (status, headline) = if reached.load(Ordering::Relaxed) {
+        (ScoreStatus::TargetReached, "Target reached")
+    } else if timed_out.load(Ordering::Relaxed) {
+        (ScoreStatus::TimedOut, "Time budget used up")
     } else {
-        0.0
+        (ScoreStatus::Cancelled, "Search cancelled")
     };
-    let final_time_str = format_duration(elapsed);
-    println!(
-        "Search completed: {count} addresses found, {final_checked} total checked, {final_speed:.0} addr/sec average, {final_time_str}"
+    print_summary(
+        &format!(
+            "{headline}: best {} zero bytes",
+            best_score.load(Ordering::Relaxed)
+        ),
+        total_checked.load(Ordering::Relaxed),
+        start_time.elapsed(),
     );
+    ScoreOutcome {
+        status,
+        best: best.into_inner().expect("Mutex should not be poisoned"),
+    }
+}
 
-    Arc::try_unwrap(found_addresses)
-        .expect("There should be no more references to the found_addresses")
-        .into_inner()
-        .expect("Mutex should not be poisoned")
+/// Mine CREATE2 salts in parallel; see [`Search::create2`]. Setting
+/// `cancel` stops early, with the salts found so far in the outcome. Matches
+/// and progress are printed to stdout; use [`Search`] to handle them
+/// yourself.
+pub fn find_create2_salts_parallel(
+    count: usize,
+    rule: &MatchRule,
+    threads: usize,
+    deployer: &[u8; 20],
+    init_code_hash: &[u8; 32],
+    cancel: &AtomicBool,
+) -> SearchOutcome {
+    let search = Search::new(rule.clone())
+        .count(count)
+        .threads(threads)
+        .create2(*deployer, *init_code_hash);
+    say!(
+        "Using {} CPU threads for parallel processing",
+        search.thread_count()
+    );
+    let observer = StdoutObserver::new(count).with_difficulty(Difficulty::from_rule(rule));
+    search.run_with(cancel, &observer)
 }
 
 #[cfg(test)]
@@ -174,7 +1180,16 @@ mod tests {
         // Prefix "0" matches ~1/16 of addresses, so this completes quickly
         // but still exercises the real hot loop and the race-resolution
         // around the last slot.
-        let found = find_addresses_parallel(5, &Some(vec!["0".to_string()]), &None, 4);
+        let rule = MatchRule::new(&["0"], &[]).unwrap();
+        let found = find_addresses_parallel(
+            5,
+            &rule,
+            4,
+            SearchMode::Eoa,
+            Some(1),
+            &AtomicBool::new(false),
+        )
+        .addresses;
         assert_eq!(
             found.len(),
             5,
@@ -190,4 +1205,414 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn case_sensitive_search_reports_checksummed_matches() {
+        // One letter nibble with a fixed case: ~1/32 of addresses qualify.
+        let rule = MatchRule::new_case_sensitive(&["A"], &[]).unwrap();
+        let found = find_addresses_parallel(
+            2,
+            &rule,
+            2,
+            SearchMode::Eoa,
+            Some(2),
+            &AtomicBool::new(false),
+        )
+        .addresses;
+        assert_eq!(found.len(), 2);
+        for f in &found {
+            assert!(
+                f.address.starts_with("0xA"),
+                "address {} does not start with checksummed A",
+                f.address
+            );
+        }
+    }
+
+    #[test]
+    fn create_mode_matches_contract_not_deployer() {
+        use crate::crypto::private_key_to_address_bytes;
+
+        let rule = MatchRule::new(&["0"], &[]).unwrap();
+        let found = find_addresses_parallel(
+            3,
+            &rule,
+            2,
+            SearchMode::Create { nonce: 5 },
+            Some(5),
+            &AtomicBool::new(false),
+        )
+        .addresses;
+        assert_eq!(found.len(), 3);
+        for f in &found {
+            let deployer = private_key_to_address_bytes(&f.private_key);
+            assert_eq!(f.address, address_to_hex(&deployer));
+            let contract = address_to_hex(&contract_address_bytes(&deployer, 5));
+            assert_eq!(f.contract_address.as_deref(), Some(contract.as_str()));
+            assert!(
+                contract.starts_with("0x0"),
+                "contract {contract} misses prefix"
+            );
+        }
+    }
+
+    #[test]
+    fn create2_mining_returns_salts_that_derive_to_matches() {
+        use crate::crypto::create2_address_bytes;
+
+        let deployer = [0x42u8; 20];
+        let init_code_hash = [0x17u8; 32];
+        let rule = MatchRule::new(&["00"], &[]).unwrap();
+        let found = find_create2_salts_parallel(
+            4,
+            &rule,
+            2,
+            &deployer,
+            &init_code_hash,
+            &AtomicBool::new(false),
+        );
+        assert_eq!(found.status, SearchStatus::Completed);
+        assert!(found.addresses.is_empty());
+        assert_eq!(found.salts.len(), 4);
+        for f in &found.salts {
+            let derived = create2_address_bytes(&deployer, &f.salt, &init_code_hash);
+            assert_eq!(f.address, address_to_hex(&derived));
+            assert!(f.address.starts_with("0x00"), "address {}", f.address);
+        }
+    }
+
+    #[test]
+    fn best_address_stops_at_target_with_increasing_improvements() {
+        use crate::crypto::{private_key_to_address_bytes, ZeroMetric};
+
+        let scores = Mutex::new(Vec::new());
+        let best = find_best_address_parallel(
+            ZeroByteScorer::new(ZeroMetric::Leading),
+            2,
+            SearchMode::Eoa,
+            ScoreStop {
+                // One leading zero byte is a 1/256 event.
+                target: Some(1),
+                time_budget: None,
+            },
+            Some(6),
+            &AtomicBool::new(false),
+            |scored| scores.lock().unwrap().push(scored.score),
+        );
+        assert_eq!(best.status, ScoreStatus::TargetReached);
+        let best = best.best.expect("a target of 1 is always reached");
+
+        assert!(best.score >= 1);
+        let addr = private_key_to_address_bytes(&best.found.private_key);
+        assert_eq!(addr[0], 0);
+        assert_eq!(best.found.address, address_to_hex(&addr));
+
+        let scores = scores.into_inner().unwrap();
+        // An address without a zero byte is never reported.
+        assert!(
+            scores.first().is_some_and(|&first| first >= 1),
+            "{scores:?}"
+        );
+        assert_eq!(scores.last(), Some(&best.score));
+        assert!(scores.windows(2).all(|w| w[0] < w[1]), "{scores:?}");
+    }
+
+    #[test]
+    fn best_address_honours_time_budget() {
+        use crate::crypto::ZeroMetric;
+
+        let start = Instant::now();
+        let best = find_best_address_parallel(
+            ZeroByteScorer::new(ZeroMetric::Leading),
+            1,
+            SearchMode::Eoa,
+            ScoreStop {
+                target: Some(20),
+                time_budget: Some(Duration::from_millis(500)),
+            },
+            Some(7),
+            &AtomicBool::new(false),
+            |_| {},
+        );
+        assert_eq!(best.status, ScoreStatus::TimedOut);
+        assert!(best.best.is_some_and(|best| best.score >= 1));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn best_address_reports_a_cancelled_search() {
+        use crate::crypto::ZeroMetric;
+
+        let outcome = find_best_address_parallel(
+            ZeroByteScorer::new(ZeroMetric::Total),
+            2,
+            SearchMode::Eoa,
+            ScoreStop::default(),
+            Some(8),
+            &AtomicBool::new(true),
+            |_| panic!("a cancelled search checks nothing"),
+        );
+        assert_eq!(outcome.status, ScoreStatus::Cancelled);
+        assert!(outcome.best.is_none());
+    }
+
+    #[test]
+    fn pattern_rule_drives_the_hot_loop() {
+        // Any two identical nibbles anywhere: nearly every address qualifies.
+        let rule = MatchRule::new(&[] as &[&str], &[])
+            .unwrap()
+            .with_patterns(&["~2", "^?0"])
+            .unwrap();
+        let found = find_addresses_parallel(
+            3,
+            &rule,
+            2,
+            SearchMode::Eoa,
+            Some(3),
+            &AtomicBool::new(false),
+        );
+        assert_eq!(found.status, SearchStatus::Completed);
+        assert_eq!(found.addresses.len(), 3);
+    }
+
+    #[test]
+    fn seeded_search_is_reproducible() {
+        let rule = MatchRule::new(&["00"], &[]).unwrap();
+        let search = |seed: u64| {
+            Search::new(rule.clone())
+                .count(3)
+                .threads(1)
+                .seed(seed)
+                .run(&Recorder::default())
+                .addresses
+                .into_iter()
+                .map(|f| (f.private_key, f.address.clone()))
+                .collect::<Vec<_>>()
+        };
+        let first = search(42);
+        assert_eq!(first.len(), 3);
+        assert_eq!(search(42), first);
+        assert_ne!(search(43), first);
+    }
+
+    #[test]
+    fn split_key_search_never_needs_the_private_key() {
+        use crate::crypto::{
+            combine_split_key, generate_private_key, private_key_to_address_bytes,
+        };
+
+        let user = generate_private_key();
+        let public = PublicKey::from_secret_key(&secp256k1::Secp256k1::new(), &user);
+        let rule = MatchRule::new(&["0"], &[]).unwrap();
+        let outcome = Search::new(rule)
+            .count(2)
+            .threads(2)
+            .split_key(public)
+            .run(&Recorder::default());
+        assert_eq!(outcome.addresses.len(), 2);
+        for f in &outcome.addresses {
+            // The search reports offsets, not keys for the found address...
+            assert_ne!(
+                address_to_hex(&private_key_to_address_bytes(&f.private_key)),
+                f.address
+            );
+            // ...which only the holder of `user` can turn into one.
+            let combined = combine_split_key(&user, &f.private_key).unwrap();
+            let address = address_to_hex(&private_key_to_address_bytes(&combined));
+            assert_eq!(address, f.address);
+            assert!(address.starts_with("0x0"), "{address}");
+        }
+    }
+
+    #[test]
+    fn mnemonic_search_records_recoverable_accounts() {
+        use crate::crypto::{private_key_to_address_bytes, ExtendedKey};
+
+        let rule = MatchRule::new(&["0"], &[]).unwrap();
+        let recorder = Recorder::default();
+        let outcome = Search::new(rule)
+            .count(2)
+            .threads(2)
+            .mnemonics(MnemonicSearch::new(12, 8).unwrap())
+            .run(&recorder);
+        assert_eq!(outcome.status, SearchStatus::Completed);
+        let found = outcome.mnemonics;
+        assert_eq!(found.len(), 2);
+        // Observers that only handle `on_match` still see every match.
+        let mut matches = recorder.matches.into_inner().unwrap();
+        matches.sort();
+        let mut addresses: Vec<_> = outcome
+            .addresses
+            .iter()
+            .map(|f| f.address.clone())
+            .collect();
+        addresses.sort();
+        assert_eq!(
+            matches.into_iter().map(|(a, _)| a).collect::<Vec<_>>(),
+            addresses
+        );
+        for f in &found {
+            let mnemonic = bip39::Mnemonic::parse(&f.mnemonic).unwrap();
+            assert_eq!(mnemonic.word_count(), 12);
+            let index: u32 = f
+                .derivation_path
+                .strip_prefix("m/44'/60'/0'/0/")
+                .unwrap()
+                .parse()
+                .unwrap();
+            assert!(index < 8);
+            let key = ExtendedKey::master(&mnemonic.to_seed(""))
+                .unwrap()
+                .derive(&f.derivation_path)
+                .unwrap();
+            assert_eq!(key.secret(), f.found.private_key);
+            let address = address_to_hex(&private_key_to_address_bytes(&key.secret()));
+            assert_eq!(address, f.found.address);
+            assert!(address.starts_with("0x0"), "{address}");
+        }
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        matches: Mutex<Vec<(String, usize)>>,
+        finished: Mutex<Vec<(SearchStatus, SearchProgress)>>,
+    }
+
+    impl SearchObserver for Recorder {
+        fn on_match(&self, found: &FoundAddress, progress: SearchProgress) {
+            self.matches
+                .lock()
+                .unwrap()
+                .push((found.address.clone(), progress.found));
+        }
+
+        fn on_finish(&self, outcome: &SearchOutcome, progress: SearchProgress) {
+            self.finished
+                .lock()
+                .unwrap()
+                .push((outcome.status, progress));
+        }
+    }
+
+    #[test]
+    fn mnemonic_search_rejects_invalid_settings() {
+        assert!(MnemonicSearch::new(24, 1 << 31).is_ok());
+        for (word_count, indexes) in [(13, 1), (0, 1), (27, 1), (12, 0), (12, (1 << 31) + 1)] {
+            assert!(
+                MnemonicSearch::new(word_count, indexes).is_err(),
+                "{word_count} words, {indexes} indexes"
+            );
+        }
+    }
+    #[test]
+    fn resumed_search_continues_from_prior_progress() {
+        let rule = MatchRule::new(&["0"], &[]).unwrap();
+        let prior = SearchProgress {
+            found: 2,
+            checked: 1_000,
+            elapsed: Duration::from_secs(60),
+        };
+        let recorder = Recorder::default();
+        let outcome = Search::new(rule)
+            .count(5)
+            .threads(2)
+            .seed(8)
+            .resume_from(prior)
+            .run(&recorder);
+
+        // Only the remaining three are searched for, numbered after the prior two.
+        assert_eq!(outcome.addresses.len(), 3);
+        let mut matches = recorder.matches.into_inner().unwrap();
+        matches.sort_by_key(|(_, index)| *index);
+        assert_eq!(
+            matches.iter().map(|(_, index)| *index).collect::<Vec<_>>(),
+            [3, 4, 5]
+        );
+        for f in &outcome.addresses {
+            assert!(matches.iter().any(|(a, _)| a == &f.address));
+        }
+
+        // on_finish fires once, with cumulative totals.
+        let finished = recorder.finished.into_inner().unwrap();
+        assert_eq!(finished.len(), 1);
+        let (status, last) = finished[0];
+        assert_eq!(status, SearchStatus::Completed);
+        assert_eq!(last.found, 5);
+        assert!(last.checked > 1_000);
+        assert!(last.elapsed >= Duration::from_secs(60));
+    }
+
+    #[test]
+    fn spawned_search_can_be_cancelled() {
+        // An impossible-in-practice prefix, so only cancellation stops it.
+        let rule = MatchRule::new(&["0123456789abcdef"], &[]).unwrap();
+        let handle = Search::new(rule)
+            .count(3)
+            .threads(2)
+            .spawn(Recorder::default());
+        thread::sleep(Duration::from_millis(300));
+        assert!(!handle.is_finished());
+        handle.cancel();
+        let outcome = handle.join();
+        assert!(outcome.is_cancelled());
+        assert_eq!(
+            outcome.status,
+            SearchStatus::Cancelled {
+                found: 0,
+                requested: 3
+            }
+        );
+        assert!(outcome.addresses.is_empty());
+    }
+
+    #[test]
+    fn search_stops_at_time_budget() {
+        let rule = MatchRule::new(&["0123456789abcdef"], &[]).unwrap();
+        let outcome = Search::new(rule)
+            .count(1)
+            .threads(2)
+            .time_budget(Duration::from_millis(600))
+            .run(&Recorder::default());
+        assert_eq!(
+            outcome.status,
+            SearchStatus::TimedOut {
+                found: 0,
+                requested: 1
+            }
+        );
+    }
+
+    #[test]
+    fn bench_thread_counts_double_up_to_max() {
+        assert_eq!(bench_thread_counts(1), [1]);
+        assert_eq!(bench_thread_counts(4), [1, 2, 4]);
+        assert_eq!(bench_thread_counts(6), [1, 2, 4, 6]);
+    }
+
+    #[test]
+    fn benchmark_measures_each_thread_count() {
+        let rule = MatchRule::new(&["0123456789abcdef"], &[]).unwrap();
+        let reported = AtomicUsize::new(0);
+        let samples = benchmark_threads(
+            &rule,
+            SearchMode::Eoa,
+            &[1, 2],
+            Duration::from_millis(600),
+            &AtomicBool::new(false),
+            |_| {
+                reported.fetch_add(1, Ordering::Relaxed);
+            },
+        );
+        assert_eq!(reported.load(Ordering::Relaxed), 2);
+        assert_eq!(
+            samples.iter().map(|s| s.threads).collect::<Vec<_>>(),
+            [1, 2]
+        );
+        for s in &samples {
+            assert!(s.checked > 0);
+            assert!(s.elapsed_secs >= 0.5);
+            assert!(s.keys_per_sec > 0.0);
+        }
+        assert_eq!(samples[0].efficiency, 1.0);
+    }
 }
@@ -1,15 +1,207 @@
+use aes::cipher::{KeyIvInit, StreamCipher};
 use chrono::Utc;
-use rand;
+use eth_keystore::{CipherparamsJson, CryptoJson, EthKeystore, KdfType, KdfparamsType};
+use hmac::Hmac;
+use rand::RngCore;
+use secp256k1::SecretKey;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
 use std::path::{Path, PathBuf};
+use uuid::Uuid;
+use zeroize::Zeroizing;
 
+use crate::crypto::{keccak256, private_key_to_address};
+use crate::utils::{create_private_dir, write_private_file};
+
+type Aes128Ctr = ctr::Ctr128BE<aes::Aes128>;
+
+/// Smallest scrypt `N` accepted. Cheap enough for test keystores, still
+/// far from free to brute-force.
+pub const MIN_SCRYPT_N: u32 = 1 << 10;
+/// Smallest PBKDF2 iteration count accepted.
+pub const MIN_PBKDF2_ITERATIONS: u32 = 10_000;
+/// Largest scrypt `N` accepted, four times geth's standard `2^18`.
+pub const MAX_SCRYPT_N: u32 = 1 << 20;
+/// Largest scrypt `r·p` accepted.
+pub const MAX_SCRYPT_RP: u64 = 64;
+/// Most memory scrypt may need (`128·r·N` bytes).
+pub const MAX_SCRYPT_MEMORY: u64 = 1 << 30;
+/// Largest PBKDF2 iteration count accepted.
+pub const MAX_PBKDF2_ITERATIONS: u32 = 10_000_000;
+/// Accepted salt lengths in bytes.
+pub const SALT_LEN_RANGE: std::ops::RangeInclusive<usize> = 16..=64;
+
+/// Key derivation function for `--kdf`.
+#[derive(clap::ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum KdfKind {
+    Scrypt,
+    Pbkdf2,
+}
+
+/// Key derivation function turning the password into the encryption key,
+/// with its cost parameters.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(tag = "kdf", rename_all = "lowercase")]
+pub enum Kdf {
+    Scrypt {
+        n: u32,
+        r: u32,
+        p: u32,
+    },
+    /// PBKDF2-HMAC-SHA256.
+    Pbkdf2 {
+        iterations: u32,
+    },
+}
+
+impl Kdf {
+    /// The KDF and cost a keystore file asks for.
+    fn of(params: &KdfparamsType) -> Self {
+        match *params {
+            KdfparamsType::Scrypt { n, r, p, .. } => Kdf::Scrypt { n, r, p },
+            KdfparamsType::Pbkdf2 { c, .. } => Kdf::Pbkdf2 { iterations: c },
+        }
+    }
+
+    /// Reject costs above the maximums above, so neither a typo nor a
+    /// crafted or corrupt keystore can make us allocate or compute without
+    /// bound.
+    fn check_max_cost(&self) -> Result<(), String> {
+        match *self {
+            Kdf::Scrypt { n, r, p } => {
+                let (n, r, p) = (u64::from(n), u64::from(r), u64::from(p));
+                if n > u64::from(MAX_SCRYPT_N) {
+                    return Err(format!("scrypt N must be at most {MAX_SCRYPT_N} (got {n})"));
+                }
+                if r * p > MAX_SCRYPT_RP {
+                    return Err(format!(
+                        "scrypt r·p must be at most {MAX_SCRYPT_RP} (got r={r}, p={p})"
+                    ));
+                }
+                if 128 * r * n > MAX_SCRYPT_MEMORY {
+                    return Err(format!(
+                        "scrypt N={n}, r={r} needs more than {} MiB",
+                        MAX_SCRYPT_MEMORY >> 20
+                    ));
+                }
+            }
+            Kdf::Pbkdf2 { iterations } => {
+                if iterations > MAX_PBKDF2_ITERATIONS {
+                    return Err(format!(
+                        "PBKDF2 needs at most {MAX_PBKDF2_ITERATIONS} iterations (got {iterations})"
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How [`generate_keystore`] encrypts keys. The default matches geth's
+/// light scrypt setting (`N = 8192`, `r = 8`, `p = 1`, 32-byte salt).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeystoreOptions {
+    #[serde(flatten)]
+    pub kdf: Kdf,
+    pub salt_len: usize,
+}
+
+impl Default for KeystoreOptions {
+    fn default() -> Self {
+        Self {
+            kdf: Kdf::Scrypt {
+                n: 8192,
+                r: 8,
+                p: 1,
+            },
+            salt_len: 32,
+        }
+    }
+}
+
+impl KeystoreOptions {
+    /// Check the parameters against the minimums and maximums above and what
+    /// the KDF itself supports.
+    pub fn validate(&self) -> Result<(), String> {
+        match self.kdf {
+            Kdf::Scrypt { n, r, p } => {
+                if !n.is_power_of_two() || n < MIN_SCRYPT_N {
+                    return Err(format!(
+                        "scrypt N must be a power of two of at least {MIN_SCRYPT_N} (got {n})"
+                    ));
+                }
+                scrypt::Params::new(n.trailing_zeros() as u8, r, p)
+                    .map_err(|_| format!("invalid scrypt parameters N={n}, r={r}, p={p}"))?;
+            }
+            Kdf::Pbkdf2 { iterations } => {
+                if iterations < MIN_PBKDF2_ITERATIONS {
+                    return Err(format!(
+                        "PBKDF2 needs at least {MIN_PBKDF2_ITERATIONS} iterations (got {iterations})"
+                    ));
+                }
+            }
+        }
+        self.kdf.check_max_cost()?;
+        if !SALT_LEN_RANGE.contains(&self.salt_len) {
+            return Err(format!(
+                "keystore salt must be {} to {} bytes (got {})",
+                SALT_LEN_RANGE.start(),
+                SALT_LEN_RANGE.end(),
+                self.salt_len
+            ));
+        }
+        Ok(())
+    }
+
+    /// Derive the 32-byte key and the keystore's `kdfparams` for `salt`.
+    fn derive_key(
+        &self,
+        password: &str,
+        salt: Vec<u8>,
+    ) -> Result<(Zeroizing<[u8; 32]>, KdfparamsType), String> {
+        let mut key = Zeroizing::new([0u8; 32]);
+        let params = match self.kdf {
+            Kdf::Scrypt { n, r, p } => {
+                let params = scrypt::Params::new(n.trailing_zeros() as u8, r, p)
+                    .map_err(|_| format!("invalid scrypt parameters N={n}, r={r}, p={p}"))?;
+                scrypt::scrypt(password.as_bytes(), &salt, &params, &mut *key)
+                    .map_err(|e| format!("scrypt failed: {e}"))?;
+                KdfparamsType::Scrypt {
+                    dklen: 32,
+                    n,
+                    p,
+                    r,
+                    salt,
+                }
+            }
+            Kdf::Pbkdf2 { iterations } => {
+                pbkdf2::pbkdf2::<Hmac<Sha256>>(password.as_bytes(), &salt, iterations, &mut *key);
+                KdfparamsType::Pbkdf2 {
+                    c: iterations,
+                    dklen: 32,
+                    prf: "hmac-sha256".to_string(),
+                    salt,
+                }
+            }
+        };
+        Ok((key, params))
+    }
+}
+
+/// Encrypt `private_key` into a V3 keystore file named
+/// `UTC--<timestamp>--<address>` in `keystore_dir`, with an added `address`
+/// field as geth writes it.
 pub fn generate_keystore(
     private_key: &[u8],
     password: &str,
     keystore_dir: &Path,
     address: &str,
+    options: &KeystoreOptions,
 ) -> Result<PathBuf, String> {
+    options.validate()?;
     if !keystore_dir.exists() {
-        std::fs::create_dir_all(keystore_dir)
+        create_private_dir(keystore_dir)
             .map_err(|e| format!("Failed to create keystore directory: {e}"))?;
     }
 
@@ -19,22 +211,34 @@ pub fn generate_keystore(
     let filename = format!("UTC--{timestamp}--{clean_address}");
 
     let mut rng = rand::thread_rng();
+    let mut salt = vec![0u8; options.salt_len];
+    rng.fill_bytes(&mut salt);
+    let mut iv = vec![0u8; 16];
+    rng.fill_bytes(&mut iv);
 
-    eth_keystore::encrypt_key(
-        keystore_dir,
-        &mut rng,
-        private_key,
-        password,
-        Some(filename.as_str()),
-    )
-    .map_err(|e| format!("Failed to create keystore file: {e}"))?;
+    let (key, kdfparams) = options.derive_key(password, salt)?;
+    let mut ciphertext = private_key.to_vec();
+    Aes128Ctr::new(key[..16].into(), iv[..].into()).apply_keystream(&mut ciphertext);
+    let mac = keccak256(&Zeroizing::new([&key[16..], &ciphertext[..]].concat()));
 
-    let keystore_path = keystore_dir.join(&filename);
-
-    let contents = std::fs::read_to_string(&keystore_path)
-        .map_err(|e| format!("Failed to read keystore file: {e}"))?;
-    let mut keystore: serde_json::Value =
-        serde_json::from_str(&contents).map_err(|e| format!("Failed to parse keystore: {e}"))?;
+    let kdf = match options.kdf {
+        Kdf::Scrypt { .. } => KdfType::Scrypt,
+        Kdf::Pbkdf2 { .. } => KdfType::Pbkdf2,
+    };
+    let keystore = EthKeystore {
+        crypto: CryptoJson {
+            cipher: "aes-128-ctr".to_string(),
+            cipherparams: CipherparamsJson { iv },
+            ciphertext,
+            kdf,
+            kdfparams,
+            mac: mac.to_vec(),
+        },
+        id: Uuid::new_v4(),
+        version: 3,
+    };
+    let mut keystore = serde_json::to_value(&keystore)
+        .map_err(|e| format!("Failed to serialize keystore: {e}"))?;
     keystore
         .as_object_mut()
         .ok_or("Invalid keystore JSON")?
@@ -42,17 +246,119 @@ pub fn generate_keystore(
             "address".to_string(),
             serde_json::Value::String(clean_address.clone()),
         );
-    let updated = serde_json::to_string(&keystore)
+    let contents = serde_json::to_string(&keystore)
         .map_err(|e| format!("Failed to serialize keystore: {e}"))?;
-    std::fs::write(&keystore_path, updated)
+
+    let keystore_path = keystore_dir.join(&filename);
+    write_private_file(&keystore_path, contents.as_bytes(), false)
         .map_err(|e| format!("Failed to write keystore file: {e}"))?;
 
     Ok(keystore_path)
 }
 
+/// Addresses found in and derived from one keystore file by
+/// [`verify_keystore`], all as lowercase hex without `0x`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeystoreCheck {
+    pub path: PathBuf,
+    /// Address of the decrypted private key.
+    pub derived: String,
+    /// Address at the end of a `UTC--<timestamp>--<address>` filename.
+    pub filename_address: Option<String>,
+    /// The `address` field [`generate_keystore`] adds.
+    pub field_address: Option<String>,
+}
+
+impl KeystoreCheck {
+    /// Descriptions of every recorded address that differs from the key's.
+    /// Missing addresses are not mismatches.
+    pub fn mismatches(&self) -> Vec<String> {
+        let mut mismatches = Vec::new();
+        for (what, address) in [
+            ("filename", &self.filename_address),
+            ("address field", &self.field_address),
+        ] {
+            if let Some(address) = address.as_ref().filter(|a| **a != self.derived) {
+                mismatches.push(format!(
+                    "{what} says 0x{address} but the key is for 0x{}",
+                    self.derived
+                ));
+            }
+        }
+        mismatches
+    }
+}
+
+/// Decrypt a V3 keystore file. Its KDF cost is checked against the maximums
+/// above first.
+pub fn decrypt_keystore(path: &Path, password: &str) -> Result<SecretKey, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let keystore: EthKeystore = serde_json::from_str(&contents)
+        .map_err(|e| format!("Failed to parse {}: {e}", path.display()))?;
+    Kdf::of(&keystore.crypto.kdfparams)
+        .check_max_cost()
+        .map_err(|e| format!("Refusing to decrypt {}: {e}", path.display()))?;
+    let key = eth_keystore::decrypt_key(path, password)
+        .map(Zeroizing::new)
+        .map_err(|e| format!("Failed to decrypt {}: {e}", path.display()))?;
+    SecretKey::from_slice(&key).map_err(|e| format!("Invalid key in {}: {e}", path.display()))
+}
+
+/// Decrypt a keystore file and collect the addresses it claims next to the
+/// one its key actually has.
+pub fn verify_keystore(path: &Path, password: &str) -> Result<KeystoreCheck, String> {
+    check_keystore(path, &decrypt_keystore(path, password)?)
+}
+
+/// Collect the addresses a keystore file claims next to the one `secret`,
+/// its already decrypted key, has.
+pub fn check_keystore(path: &Path, secret: &SecretKey) -> Result<KeystoreCheck, String> {
+    let normalize = |address: &str| address.strip_prefix("0x").unwrap_or(address).to_lowercase();
+
+    let filename_address = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.rsplit_once("--"))
+        .map(|(_, address)| normalize(address))
+        .filter(|address| address.len() == 40 && address.bytes().all(|b| b.is_ascii_hexdigit()));
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let keystore: serde_json::Value = serde_json::from_str(&contents)
+        .map_err(|e| format!("Failed to parse {}: {e}", path.display()))?;
+    let field_address = keystore["address"].as_str().map(normalize);
+
+    Ok(KeystoreCheck {
+        path: path.to_path_buf(),
+        derived: normalize(&private_key_to_address(secret)),
+        filename_address,
+        field_address,
+    })
+}
+
+/// `path` itself if it is a file, otherwise the files directly inside it,
+/// sorted by name.
+pub fn keystore_files(path: &Path) -> Result<Vec<PathBuf>, String> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let entries =
+        std::fs::read_dir(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let mut files = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        if entry.path().is_file() {
+            files.push(entry.path());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::test_dir;
 
     #[test]
     fn keystore_has_address_field_and_standard_filename() {
@@ -60,10 +366,16 @@ mod tests {
         let pk_bytes = hex::decode(pk_hex).unwrap();
         let address = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
 
-        let dir = std::env::temp_dir().join("ks_test_standard_format");
-        let _ = std::fs::remove_dir_all(&dir);
+        let dir = test_dir("ks_test_standard_format");
 
-        let path = generate_keystore(&pk_bytes, "testpass", &dir, address).unwrap();
+        let path = generate_keystore(
+            &pk_bytes,
+            "testpass",
+            &dir,
+            address,
+            &KeystoreOptions::default(),
+        )
+        .unwrap();
 
         let fname = path.file_name().unwrap().to_str().unwrap();
         assert!(
@@ -90,6 +402,206 @@ mod tests {
         let decrypted = eth_keystore::decrypt_key(&path, "testpass").unwrap();
         assert_eq!(decrypted, pk_bytes);
 
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+            assert_eq!(mode(&path), 0o600);
+            assert_eq!(mode(&dir), 0o700);
+        }
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn keystores_use_the_requested_kdf_and_cost() {
+        let pk_bytes =
+            hex::decode("ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80")
+                .unwrap();
+        let address = "f39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+        let dir = test_dir("ks_test_kdf");
+
+        for (options, kdf, kdfparams) in [
+            (
+                KeystoreOptions {
+                    kdf: Kdf::Scrypt {
+                        n: MIN_SCRYPT_N,
+                        r: 8,
+                        p: 2,
+                    },
+                    salt_len: 16,
+                },
+                "scrypt",
+                serde_json::json!({"n": MIN_SCRYPT_N, "r": 8, "p": 2, "dklen": 32}),
+            ),
+            (
+                KeystoreOptions {
+                    kdf: Kdf::Pbkdf2 {
+                        iterations: MIN_PBKDF2_ITERATIONS,
+                    },
+                    salt_len: 64,
+                },
+                "pbkdf2",
+                serde_json::json!({"c": MIN_PBKDF2_ITERATIONS, "prf": "hmac-sha256", "dklen": 32}),
+            ),
+        ] {
+            let path = generate_keystore(&pk_bytes, "testpass", &dir, address, &options).unwrap();
+            let v: serde_json::Value =
+                serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+            assert_eq!(v["crypto"]["kdf"], kdf);
+            for (name, value) in kdfparams.as_object().unwrap() {
+                assert_eq!(&v["crypto"]["kdfparams"][name], value, "{kdf} {name}");
+            }
+            let salt = v["crypto"]["kdfparams"]["salt"].as_str().unwrap();
+            assert_eq!(salt.len(), options.salt_len * 2);
+            assert_eq!(v["address"], address);
+            assert_eq!(
+                eth_keystore::decrypt_key(&path, "testpass").unwrap(),
+                pk_bytes
+            );
+        }
+
+        let too_cheap = [
+            Kdf::Scrypt { n: 512, r: 8, p: 1 },
+            Kdf::Scrypt {
+                n: 3000,
+                r: 8,
+                p: 1,
+            },
+            Kdf::Scrypt {
+                n: 8192,
+                r: 0,
+                p: 1,
+            },
+            Kdf::Pbkdf2 { iterations: 1000 },
+        ];
+        let too_costly = [
+            Kdf::Scrypt {
+                n: 1 << 31,
+                r: 8,
+                p: 1,
+            },
+            Kdf::Scrypt {
+                n: 8192,
+                r: 8,
+                p: 16,
+            },
+            Kdf::Scrypt {
+                n: MAX_SCRYPT_N,
+                r: 16,
+                p: 1,
+            },
+            Kdf::Pbkdf2 {
+                iterations: u32::MAX,
+            },
+        ];
+        for kdf in too_cheap.into_iter().chain(too_costly) {
+            let options = KeystoreOptions { kdf, salt_len: 32 };
+            assert!(options.validate().is_err(), "{kdf:?}");
+        }
+        let short_salt = KeystoreOptions {
+            salt_len: 8,
+            ..KeystoreOptions::default()
+        };
+        assert!(generate_keystore(&pk_bytes, "testpass", &dir, address, &short_salt).is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn verify_reports_addresses_that_do_not_match_the_key() {
+        let pk_bytes =
+            hex::decode("ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80")
+                .unwrap();
+        let address = "f39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+        let dir = test_dir("ks_test_verify");
+
+        let path = generate_keystore(
+            &pk_bytes,
+            "testpass",
+            &dir,
+            address,
+            &KeystoreOptions::default(),
+        )
+        .unwrap();
+        let check = verify_keystore(&path, "testpass").unwrap();
+        assert_eq!(check.derived, address);
+        assert_eq!(check.filename_address.as_deref(), Some(address));
+        assert_eq!(check.field_address.as_deref(), Some(address));
+        assert!(check.mismatches().is_empty());
+        assert!(verify_keystore(&path, "wrong").is_err());
+
+        // Claim a different address in the file's `address` field.
+        let other = "70997970c51812dc3a010c7d01b50e0d17dc79c8";
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, contents.replace(address, other)).unwrap();
+        let mismatches = verify_keystore(&path, "testpass").unwrap().mismatches();
+        assert_eq!(mismatches.len(), 1);
+        assert!(mismatches[0].starts_with("address field"), "{mismatches:?}");
+
+        assert_eq!(
+            keystore_files(&dir).unwrap(),
+            keystore_files(&path).unwrap()
+        );
+        assert_eq!(keystore_files(&path).unwrap(), [path]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn decrypt_refuses_keystores_with_an_excessive_kdf_cost() {
+        let pk_bytes =
+            hex::decode("ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80")
+                .unwrap();
+        let address = "f39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+        let dir = test_dir("ks_test_kdf_cost");
+        let path = generate_keystore(
+            &pk_bytes,
+            "testpass",
+            &dir,
+            address,
+            &KeystoreOptions::default(),
+        )
+        .unwrap();
+        let keystore: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+
+        // The first would need a TiB of memory; the others, GiBs.
+        for (params, error) in [
+            (
+                serde_json::json!({"n": 1u64 << 30}),
+                "scrypt N must be at most",
+            ),
+            (
+                serde_json::json!({"r": 1 << 20}),
+                "scrypt r·p must be at most",
+            ),
+            (
+                serde_json::json!({"n": MAX_SCRYPT_N, "r": 16}),
+                "needs more than",
+            ),
+        ] {
+            let mut crafted = keystore.clone();
+            for (name, value) in params.as_object().unwrap() {
+                crafted["crypto"]["kdfparams"][name] = value.clone();
+            }
+            std::fs::write(&path, crafted.to_string()).unwrap();
+            let e = verify_keystore(&path, "testpass").unwrap_err();
+            assert!(e.contains(error), "{e}");
+        }
+
+        let mut crafted = keystore.clone();
+        crafted["crypto"]["kdf"] = serde_json::json!("pbkdf2");
+        crafted["crypto"]["kdfparams"] = serde_json::json!({
+            "c": u32::MAX,
+            "dklen": 32,
+            "prf": "hmac-sha256",
+            "salt": keystore["crypto"]["kdfparams"]["salt"],
+        });
+        std::fs::write(&path, crafted.to_string()).unwrap();
+        let e = decrypt_keystore(&path, "testpass").unwrap_err();
+        assert!(e.contains("PBKDF2 needs at most"), "{e}");
+
         let _ = std::fs::remove_dir_all(&dir);
     }
 }
@@ -1,17 +1,52 @@
 pub mod cli;
 pub mod crypto;
+pub mod dictionary;
 pub mod finder;
 pub mod keystore;
 pub mod models;
+pub mod stats;
 pub mod utils;
 
 // Re-export commonly used items
-pub use cli::{create_rule, expand_arg, get_password, print_search_info, Args};
+pub use cli::{
+    build_rule, collect_alternatives, confirm, create_rule, expand_arg, get_password,
+    keystore_options, normalize_case, output_format, parse_public_key, parse_secret_key,
+    print_difficulty, print_search_info, read_alternatives_file, read_password, read_secret_key,
+    Args, BenchArgs, CombineArgs, Command, Create2Args, KeystoreCommand, MnemonicArgs, SplitArgs,
+    ZerosArgs,
+};
 pub use crypto::{
-    address_matches, address_to_hex, generate_private_key, private_key_to_address,
-    private_key_to_address_bytes, IncrementalKeygen, MatchRule,
+    address_from_words, address_matches, address_to_checksum_hex, address_to_hex,
+    address_words_from_uncompressed, addresses_from_uncompressed, check_mnemonic_word_count,
+    combine_split_key, contract_address_bytes, create2_address_bytes, eth_account_parent,
+    generate_mnemonic, generate_private_key, keccak256, private_key_to_address,
+    private_key_to_address_bytes, public_key_to_address_bytes, seeded_private_key,
+    uncompressed_to_address_bytes, AddressWords, BatchKeygen, Create2Hasher, ExtendedKey,
+    IncrementalKeygen, MatchRule, Pattern, WordMatcher, ZeroByteScorer, ZeroMetric,
+    ETH_ACCOUNT_PATH, KECCAK_LANES, KEYGEN_BATCH_SIZE,
+};
+pub use dictionary::{hex_spellings, Dictionary, WordPosition, MAX_SPELLINGS_PER_WORD};
+pub use finder::{
+    bench_thread_counts, benchmark_threads, cancel_on_interrupt, find_addresses_parallel,
+    find_best_address_parallel, find_create2_salts_parallel, MnemonicSearch, ScoreOutcome,
+    ScoreStatus, ScoreStop, Search, SearchHandle, SearchMode, SearchObserver, SearchOutcome,
+    SearchProgress, SearchStatus, StdoutObserver, CHECKPOINT_INTERVAL,
+};
+pub use keystore::{
+    check_keystore, decrypt_keystore, generate_keystore, keystore_files, verify_keystore, Kdf,
+    KdfKind, KeystoreCheck, KeystoreOptions, MAX_PBKDF2_ITERATIONS, MAX_SCRYPT_MEMORY,
+    MAX_SCRYPT_N, MAX_SCRYPT_RP, MIN_PBKDF2_ITERATIONS, MIN_SCRYPT_N,
+};
+pub use models::{
+    BenchReport, BenchSample, Checkpoint, Create2Results, FoundAddress, FoundMnemonic, FoundSalt,
+    KeyPair, KeystoreResults, PartialKeyEntry, Password, PublicAddressEntry, Results, SaltEntry,
+    SecretHex, SplitResults,
+};
+pub use stats::Difficulty;
+pub use utils::{
+    checkpoint_filename, decrypt_results_file, ensure_output_dir, generate_filename, is_encrypted,
+    load_checkpoint, load_split_results, messages_to_stderr, open_sink, save_checkpoint,
+    save_create2_results, save_results, save_split_results, serialize_results, DocumentFile,
+    OutputFormat, RecordWriter, ResultRecord, ResultSink, ResultsCipher, ResultsKey,
+    RESULTS_SCRYPT_LOG_N, STDOUT,
 };
-pub use finder::find_addresses_parallel;
-pub use keystore::generate_keystore;
-pub use models::{FoundAddress, KeyPair, KeystoreResults, PublicAddressEntry, Results};
-pub use utils::{ensure_output_dir, generate_filename, save_results};
@@ -1,14 +1,32 @@
 use std::path::Path;
 use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use chrono::Local;
 use clap::Parser;
 use dotenv::dotenv;
+use zeroize::{Zeroize, Zeroizing};
 
 use address_finder::{
-    create_rule, ensure_output_dir, expand_arg, find_addresses_parallel, generate_filename,
-    generate_keystore, get_password, print_search_info, save_results, Args, KeyPair,
-    KeystoreResults, PublicAddressEntry, Results,
+    address_to_hex, bench_thread_counts, benchmark_threads, build_rule, cancel_on_interrupt,
+    check_keystore, checkpoint_filename, collect_alternatives, combine_split_key, confirm,
+    contract_address_bytes, create_rule, decrypt_keystore, decrypt_results_file, ensure_output_dir,
+    expand_arg, find_best_address_parallel, generate_filename, generate_keystore, get_password,
+    keccak256, keystore_files, keystore_options, load_checkpoint, load_split_results,
+    messages_to_stderr, normalize_case, open_sink, output_format, parse_public_key,
+    parse_secret_key, print_difficulty, print_search_info, private_key_to_address,
+    private_key_to_address_bytes, public_key_to_address_bytes, read_password, read_secret_key,
+    save_checkpoint, save_create2_results, save_split_results, say, verify_keystore, Args,
+    BenchArgs, BenchReport, Checkpoint, CombineArgs, Command, Create2Args, Create2Results,
+    Dictionary, Difficulty, FoundAddress, FoundMnemonic, FoundSalt, KeyPair, KeystoreCommand,
+    KeystoreOptions, KeystoreResults, MatchRule, MnemonicArgs, MnemonicSearch, OutputFormat,
+    PartialKeyEntry, Password, PublicAddressEntry, ResultRecord, ResultSink, Results,
+    ResultsCipher, ResultsKey, SaltEntry, ScoreStop, Search, SearchMode, SearchObserver,
+    SearchOutcome, SearchProgress, SearchStatus, SecretHex, SplitArgs, SplitResults,
+    StdoutObserver, WordPosition, ZeroByteScorer, ZeroMetric, ZerosArgs, CHECKPOINT_INTERVAL,
+    ETH_ACCOUNT_PATH, MAX_SPELLINGS_PER_WORD, RESULTS_SCRYPT_LOG_N, STDOUT,
 };
 
 fn main() {
@@ -18,134 +36,1512 @@ fn main() {
     // Parse command line arguments
     let args = Args::parse();
 
-    // Expand comma-separated prefix/suffix strings into Vec<String> alternatives.
-    let prefix = expand_arg(&args.prefix);
-    let suffix = expand_arg(&args.suffix);
-
-    // Create a rule string for the filename
-    let rule = create_rule(&prefix, &suffix);
+    if args.resume.is_some() && args.command.is_some() {
+        eprintln!("--resume only applies to the default address search");
+        process::exit(1);
+    }
+    let encrypts = args.encrypt_results || !args.encrypt_to.is_empty();
+    if args.resume.is_some() && (args.output.is_some() || args.format.is_some() || encrypts) {
+        eprintln!("--resume continues writing to the checkpoint's results file");
+        process::exit(1);
+    }
+    if (args.output.is_some() || args.format.is_some() || encrypts)
+        && matches!(
+            args.command,
+            Some(
+                Command::Create2(_)
+                    | Command::Bench(_)
+                    | Command::Split(_)
+                    | Command::Keystore(_)
+                    | Command::DecryptResults { .. }
+            )
+        )
+    {
+        eprintln!(
+            "--output, --format, --encrypt-results and --encrypt-to only apply to searches that save keys"
+        );
+        process::exit(1);
+    }
+    if args.output.as_deref() == Some(STDOUT) {
+        messages_to_stderr();
+    }
 
-    // Print information about the search
-    print_search_info(&prefix, &suffix, args.count);
+    match &args.command {
+        Some(Command::Create2(create2)) => run_create2(&args, create2),
+        Some(Command::Zeros(zeros)) => run_zeros(&args, zeros),
+        Some(Command::Bench(bench)) => run_bench(&args, bench),
+        Some(Command::Split(split)) => run_split(&args, split),
+        Some(Command::Combine(combine)) => run_combine(&args, combine),
+        Some(Command::Mnemonic(mnemonic)) => run_mnemonic(&args, mnemonic),
+        Some(Command::Keystore(keystore)) => run_keystore(keystore),
+        Some(Command::DecryptResults { file }) => {
+            run_decrypt_results(file, args.identity.as_deref())
+        }
+        None => run_search(&args),
+    }
+}
 
-    // Ensure output directory exists
-    if let Err(e) = ensure_output_dir(&args.output_dir) {
-        eprintln!("Error creating output directory: {e}");
+/// Install the Ctrl-C/SIGTERM handler and return the flag it sets.
+fn interrupt_flag() -> Arc<AtomicBool> {
+    let cancel = Arc::new(AtomicBool::new(false));
+    if let Err(e) = cancel_on_interrupt(Arc::clone(&cancel)) {
+        eprintln!("{e}");
         process::exit(1);
     }
+    cancel
+}
+
+/// Exit with the conventional SIGINT status if the search was interrupted.
+fn exit_if_cancelled(cancel: &AtomicBool) {
+    if cancel.load(Ordering::Relaxed) {
+        process::exit(130);
+    }
+}
+
+/// Decide which derived address (EOA or CREATE contract) is matched/scored.
+fn search_mode(args: &Args) -> SearchMode {
+    if args.contract {
+        SearchMode::Create { nonce: args.nonce }
+    } else {
+        SearchMode::Eoa
+    }
+}
 
-    // Determine keystore directory if keystore option is enabled
-    let keystore_dir = if args.keystore {
-        let dir = args
-            .keystore_dir
-            .clone()
-            .unwrap_or_else(|| format!("{}/keystore", args.output_dir));
-        if let Err(e) = ensure_output_dir(&dir) {
-            eprintln!("Error creating keystore directory: {e}");
+/// Where and how found keys are persisted: the results document, written
+/// through a [`ResultSink`], plus, in keystore mode, one encrypted keystore
+/// file per key.
+struct Output {
+    /// The results file, or [`STDOUT`].
+    filename: String,
+    format: OutputFormat,
+    /// The results file is encrypted with `--encrypt-results` or
+    /// `--encrypt-to`.
+    encrypted: bool,
+    /// age recipients of an `--encrypt-to` results file.
+    encrypted_to: Vec<String>,
+    sink: Box<dyn ResultSink>,
+    keystore_dir: Option<String>,
+    password: Option<Password>,
+    keystore_options: KeystoreOptions,
+    standard_results: Option<Results>,
+    keystore_results: Option<KeystoreResults>,
+}
+
+impl Output {
+    /// Create the output (and keystore) directories and prompt for the
+    /// keystore and results passwords if needed. Exits the process on
+    /// failure.
+    fn new(args: &Args, rule: &str) -> Self {
+        let format = output_format(args);
+        if args.keystore && !format.supports_keystore() {
+            eprintln!(
+                "--format {} needs plaintext keys and can't be used with --keystore",
+                format.name()
+            );
             process::exit(1);
         }
-        Some(dir)
-    } else {
-        None
-    };
+        // Resuming reads the results back from the encrypted document, so
+        // only the full json document can be encrypted.
+        let encrypted = args.encrypt_results || !args.encrypt_to.is_empty();
+        if encrypted && format != OutputFormat::Json {
+            eprintln!(
+                "--encrypt-results and --encrypt-to encrypt the json results document and can't be used with --format {}",
+                format.name()
+            );
+            process::exit(1);
+        }
+
+        // Ensure output directory exists
+        if let Err(e) = ensure_output_dir(&args.output_dir) {
+            eprintln!("Error creating output directory: {e}");
+            process::exit(1);
+        }
+
+        // Determine keystore directory if keystore option is enabled
+        let keystore_dir = if args.keystore {
+            let dir = args
+                .keystore_dir
+                .clone()
+                .unwrap_or_else(|| format!("{}/keystore", args.output_dir));
+            if let Err(e) = ensure_output_dir(&dir) {
+                eprintln!("Error creating keystore directory: {e}");
+                process::exit(1);
+            }
+            Some(dir)
+        } else {
+            None
+        };
+
+        // Validate the keystore encryption settings before asking for a password
+        let keystore_options = if args.keystore {
+            match keystore_options(args) {
+                Ok(options) => options,
+                Err(e) => {
+                    eprintln!("{e}");
+                    process::exit(1);
+                }
+            }
+        } else {
+            KeystoreOptions::default()
+        };
+
+        // Get password if keystore option is enabled
+        let password = if args.keystore {
+            match get_password("keystore encryption") {
+                Ok(pwd) => Some(pwd),
+                Err(e) => {
+                    eprintln!("Error getting password: {e}");
+                    process::exit(1);
+                }
+            }
+        } else {
+            None
+        };
+
+        // Get the results password, or check the recipients' public keys
+        let cipher = if !args.encrypt_to.is_empty() {
+            match ResultsCipher::recipients(&args.encrypt_to) {
+                Ok(cipher) => Some(cipher),
+                Err(e) => {
+                    eprintln!("{e}");
+                    process::exit(1);
+                }
+            }
+        } else if args.encrypt_results {
+            match get_password("results encryption") {
+                Ok(pwd) => Some(ResultsCipher::passphrase(
+                    pwd.expose(),
+                    RESULTS_SCRYPT_LOG_N,
+                )),
+                Err(e) => {
+                    eprintln!("Error getting password: {e}");
+                    process::exit(1);
+                }
+            }
+        } else {
+            None
+        };
+
+        // Open the results file, or stdout
+        let filename = args.output.clone().unwrap_or_else(|| {
+            let extension = if encrypted {
+                format!("{}.age", format.extension())
+            } else {
+                format.extension().to_string()
+            };
+            generate_filename(&args.output_dir, rule, &extension)
+        });
+        let sink = match open_sink(&filename, format, false, cipher) {
+            Ok(sink) => sink,
+            Err(e) => {
+                eprintln!("{e}");
+                process::exit(1);
+            }
+        };
+
+        // Initialize results based on whether keystore mode is enabled
+        let timestamp = Local::now().to_rfc3339();
+        let contract_nonce = args.contract.then_some(args.nonce);
+
+        let standard_results = if !args.keystore {
+            Some(Results {
+                timestamp: timestamp.clone(),
+                rule: rule.to_string(),
+                contract_nonce,
+                key_pairs: Vec::new(),
+            })
+        } else {
+            None
+        };
+
+        let keystore_results = if args.keystore {
+            Some(KeystoreResults {
+                timestamp,
+                rule: rule.to_string(),
+                contract_nonce,
+                encryption: Some(keystore_options),
+                addresses: Vec::new(),
+            })
+        } else {
+            None
+        };
+
+        Self {
+            filename,
+            format,
+            encrypted,
+            encrypted_to: args.encrypt_to.clone(),
+            sink,
+            keystore_dir,
+            password,
+            keystore_options,
+            standard_results,
+            keystore_results,
+        }
+    }
+
+    /// Pick up the results and keystore directory of a checkpointed job,
+    /// prompting for the keystore password again if needed. The results of
+    /// an encrypted job are read back with its results password, or with
+    /// the `identity` file of an `--encrypt-to` job. Exits the process on
+    /// failure.
+    fn resume(checkpoint: &Checkpoint, identity: Option<&str>) -> Self {
+        if let Some(dir) = Path::new(&checkpoint.results_file).parent() {
+            if let Err(e) = ensure_output_dir(&dir.display().to_string()) {
+                eprintln!("Error creating output directory: {e}");
+                process::exit(1);
+            }
+        }
+
+        let password = if let Some(dir) = &checkpoint.keystore_dir {
+            if let Err(e) = ensure_output_dir(dir) {
+                eprintln!("Error creating keystore directory: {e}");
+                process::exit(1);
+            }
+            match get_password("keystore encryption") {
+                Ok(pwd) => Some(pwd),
+                Err(e) => {
+                    eprintln!("Error getting password: {e}");
+                    process::exit(1);
+                }
+            }
+        } else {
+            None
+        };
 
-    // Get password if keystore option is enabled
-    let password = if args.keystore {
-        match get_password() {
-            Ok(pwd) => Some(pwd),
+        // An encrypted job's results are only in its results file
+        let mut standard_results = checkpoint.results.clone();
+        let mut keystore_results = checkpoint.keystore_results.clone();
+        let cipher = if checkpoint.encrypted {
+            let decrypted = if checkpoint.encrypted_to.is_empty() {
+                read_password("results")
+                    .map_err(|e| format!("Error reading password: {e}"))
+                    .and_then(|passphrase| {
+                        let cipher =
+                            ResultsCipher::passphrase(passphrase.expose(), RESULTS_SCRYPT_LOG_N);
+                        let key = ResultsKey::Passphrase(passphrase);
+                        decrypt_results_file(&checkpoint.results_file, &key)
+                            .map(|document| (cipher, document))
+                    })
+            } else {
+                identity
+                    .ok_or_else(|| {
+                        format!(
+                            "{} is encrypted to {}; pass --identity with a matching age identity file to resume",
+                            checkpoint.results_file,
+                            checkpoint.encrypted_to.join(", ")
+                        )
+                    })
+                    .and_then(ResultsKey::identity_file)
+                    .and_then(|key| decrypt_results_file(&checkpoint.results_file, &key))
+                    .and_then(|document| {
+                        ResultsCipher::recipients(&checkpoint.encrypted_to)
+                            .map(|cipher| (cipher, document))
+                    })
+            };
+            let (cipher, document) = match decrypted {
+                Ok(decrypted) => decrypted,
+                Err(e) => {
+                    eprintln!("{e}");
+                    process::exit(1);
+                }
+            };
+            let parsed = if checkpoint.keystore_dir.is_some() {
+                serde_json::from_str(&document).map(|results| keystore_results = Some(results))
+            } else {
+                serde_json::from_str(&document).map(|results| standard_results = Some(results))
+            };
+            if let Err(e) = parsed {
+                eprintln!("Error parsing {}: {e}", checkpoint.results_file);
+                process::exit(1);
+            }
+            Some(cipher)
+        } else {
+            None
+        };
+
+        // Keep encrypting with the job's settings; checkpoints from before
+        // they were recorded used the defaults.
+        let keystore_options = keystore_results
+            .as_mut()
+            .map(|results| *results.encryption.get_or_insert_with(Default::default))
+            .unwrap_or_default();
+        if let Err(e) = keystore_options.validate() {
+            eprintln!("{}: {e}", checkpoint.results_file);
+            process::exit(1);
+        }
+
+        let sink = match open_sink(&checkpoint.results_file, checkpoint.format, true, cipher) {
+            Ok(sink) => sink,
             Err(e) => {
-                eprintln!("Error getting password: {e}");
+                eprintln!("{e}");
                 process::exit(1);
             }
+        };
+
+        Self {
+            filename: checkpoint.results_file.clone(),
+            format: checkpoint.format,
+            encrypted: checkpoint.encrypted,
+            encrypted_to: checkpoint.encrypted_to.clone(),
+            sink,
+            keystore_dir: checkpoint.keystore_dir.clone(),
+            password,
+            keystore_options,
+            standard_results,
+            keystore_results,
+        }
+    }
+
+    /// Number of keys recorded so far
+    fn found_count(&self) -> usize {
+        match (&self.standard_results, &self.keystore_results) {
+            (_, Some(keystore_results)) => keystore_results.addresses.len(),
+            (Some(results), None) => results.key_pairs.len(),
+            (None, None) => 0,
+        }
+    }
+
+    /// Add a found key to the results, writing its keystore file first when
+    /// keystore mode is enabled. Exits the process on failure.
+    fn record(&mut self, found: FoundAddress, mut notes: KeyNotes) {
+        let FoundAddress {
+            address,
+            contract_address,
+            ..
+        } = &found;
+        let (word, derivation_path) = (&notes.word, &notes.derivation_path);
+        let private_key_bytes = Zeroizing::new(found.private_key.secret_bytes());
+
+        // If not in keystore mode, add private key to results
+        if let Some(results) = &mut self.standard_results {
+            results.key_pairs.push(KeyPair {
+                private_key: SecretHex::from_bytes(&private_key_bytes),
+                public_address: address.clone(),
+                contract_address: contract_address.clone(),
+                word: word.clone(),
+                mnemonic: notes.mnemonic.take(),
+                derivation_path: derivation_path.clone(),
+            });
+        }
+
+        // Generate keystore file if requested
+        if let (Some(dir), Some(pwd)) = (&self.keystore_dir, &self.password) {
+            match generate_keystore(
+                &*private_key_bytes,
+                pwd.expose(),
+                Path::new(dir),
+                address,
+                &self.keystore_options,
+            ) {
+                Ok(path) => {
+                    say!("Keystore file created: {}", path.display());
+
+                    // Add to keystore results
+                    if let Some(keystore_results) = &mut self.keystore_results {
+                        keystore_results.addresses.push(PublicAddressEntry {
+                            public_address: address.clone(),
+                            keystore_file: path.display().to_string(),
+                            contract_address: contract_address.clone(),
+                            word: word.clone(),
+                            derivation_path: derivation_path.clone(),
+                        });
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Error creating keystore file: {e}");
+                    process::exit(1);
+                }
+            }
+        }
+
+        // Stream the new entry right away to sinks that write per record
+        let record = match (&self.standard_results, &self.keystore_results) {
+            (_, Some(keystore_results)) => keystore_results
+                .addresses
+                .last()
+                .map(ResultRecord::Keystore),
+            (Some(results), None) => results.key_pairs.last().map(ResultRecord::KeyPair),
+            (None, None) => None,
+        };
+        if let Some(record) = record {
+            if let Err(e) = self.sink.record(record) {
+                eprintln!("{e}");
+                process::exit(1);
+            }
+        }
+        notes.mnemonic.zeroize();
+    }
+
+    /// Save the results document to the sink. Exits the process on failure.
+    fn save(&mut self) {
+        if let Err(e) = self
+            .sink
+            .save(&self.standard_results, &self.keystore_results)
+        {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+    }
+}
+
+/// What is saved with a found key besides its addresses.
+#[derive(Default)]
+struct KeyNotes {
+    /// Dictionary word the address spells, in `--words` mode.
+    word: Option<String>,
+    /// Mnemonic of a `mnemonic` search key; wiped unsaved in keystore mode.
+    mnemonic: Option<String>,
+    derivation_path: Option<String>,
+}
+
+/// The prefix/suffix/pattern alternatives and word list from the command
+/// line, expanded for display and compiled into a [`MatchRule`] for the hot
+/// loop.
+struct Criteria {
+    prefix: Option<Vec<String>>,
+    suffix: Option<Vec<String>>,
+    pattern: Option<Vec<String>>,
+    dictionary: Option<Dictionary>,
+    rule: MatchRule,
+}
+
+impl Criteria {
+    /// Exits the process if the alternatives don't form a valid rule.
+    fn from_args(args: &Args) -> Self {
+        // Expand comma-separated strings and alternative files into
+        // Vec<String> alternatives. Unless matching is case-sensitive, fold
+        // to lowercase up front.
+        let collect = |inline, file| match collect_alternatives(inline, file) {
+            Ok(alts) => normalize_case(alts, args.case_sensitive),
+            Err(e) => {
+                eprintln!("{e}");
+                process::exit(1);
+            }
+        };
+        let dictionary = args.words.as_deref().map(|path| {
+            match Dictionary::from_file(path, args.min_word_length, args.word_position) {
+                Ok(dictionary) => {
+                    let skipped = dictionary.too_ambiguous();
+                    if !skipped.is_empty() {
+                        eprintln!(
+                            "Warning: skipping {} word(s) with more than {MAX_SPELLINGS_PER_WORD} hex spellings: {}",
+                            skipped.len(),
+                            skipped.join(", ")
+                        );
+                    }
+                    dictionary
+                }
+                Err(e) => {
+                    eprintln!("{e}");
+                    process::exit(1);
+                }
+            }
+        });
+        Self::new(
+            collect(&args.prefix, &args.prefix_file),
+            collect(&args.suffix, &args.suffix_file),
+            normalize_case(expand_arg(&args.pattern), args.case_sensitive),
+            dictionary,
+            args.case_sensitive,
+        )
+    }
+
+    /// Exits the process if the alternatives don't form a valid rule.
+    fn new(
+        prefix: Option<Vec<String>>,
+        suffix: Option<Vec<String>>,
+        pattern: Option<Vec<String>>,
+        dictionary: Option<Dictionary>,
+        case_sensitive: bool,
+    ) -> Self {
+        // Word spellings take the place of the prefix or suffix alternatives
+        // (the CLI doesn't allow both), or become infixes.
+        let spellings = dictionary
+            .as_ref()
+            .map(|d| (d.position, Some(d.spellings())));
+        let rule = match &spellings {
+            Some((WordPosition::Start, words)) => {
+                build_rule(words, &suffix, &pattern, case_sensitive)
+            }
+            Some((WordPosition::End, words)) => {
+                build_rule(&prefix, words, &pattern, case_sensitive)
+            }
+            Some((WordPosition::Anywhere, words)) => {
+                build_rule(&prefix, &suffix, &pattern, case_sensitive)
+                    .and_then(|rule| rule.with_infixes(words.as_deref().unwrap_or(&[])))
+            }
+            None => build_rule(&prefix, &suffix, &pattern, case_sensitive),
+        };
+        let rule = match rule {
+            Ok(rule) => rule,
+            Err(e) => {
+                eprintln!("Invalid prefix/suffix/pattern: {e}");
+                process::exit(1);
+            }
+        };
+        Self {
+            prefix,
+            suffix,
+            pattern,
+            dictionary,
+            rule,
+        }
+    }
+
+    /// Rule string for the results filename
+    fn rule_name(&self) -> String {
+        let rule = create_rule(&self.prefix, &self.suffix, &self.pattern);
+        let Some(dictionary) = &self.dictionary else {
+            return rule;
+        };
+        let words = format!(
+            "words_{}_{}",
+            dictionary.position.name(),
+            dictionary.word_count()
+        );
+        if rule == "no_rule" {
+            words
+        } else {
+            format!("{words}_{rule}")
+        }
+    }
+
+    /// The dictionary word `found` spells, in `--words` mode.
+    fn word_for(&self, found: &FoundAddress) -> Option<String> {
+        let matched = found.contract_address.as_ref().unwrap_or(&found.address);
+        self.dictionary
+            .as_ref()?
+            .word_for(matched)
+            .map(String::from)
+    }
+
+    fn print_info(&self, count: usize) {
+        print_search_info(
+            &self.prefix,
+            &self.suffix,
+            &self.pattern,
+            self.dictionary.as_ref(),
+            count,
+        );
+        print_difficulty(&self.difficulty(), count);
+    }
+
+    fn difficulty(&self) -> Difficulty {
+        Difficulty::from_rule(&self.rule)
+    }
+}
+
+/// Default mode: find `count` keys whose address matches the criteria.
+///
+/// Every match is saved as soon as it is found, and a checkpoint next to the
+/// results file lets `--resume` continue the job after a crash or restart.
+fn run_search(args: &Args) {
+    let checkpoint = args.resume.as_deref().map(|path| {
+        let checkpoint = match load_checkpoint(path) {
+            Ok(checkpoint) => checkpoint,
+            Err(e) => {
+                eprintln!("{e}");
+                process::exit(1);
+            }
+        };
+        if checkpoint.results_file == STDOUT {
+            messages_to_stderr();
+        }
+        if checkpoint.completed {
+            say!(
+                "Checkpoint {path} belongs to a completed search; results are in {}",
+                checkpoint.results_file
+            );
+            process::exit(0);
+        }
+        say!("Resuming search from {path}; search criteria are taken from the checkpoint");
+        checkpoint
+    });
+
+    let (criteria, mode, count, rule, seed) = match &checkpoint {
+        Some(cp) => (
+            Criteria::new(
+                cp.prefix.clone(),
+                cp.suffix.clone(),
+                cp.pattern.clone(),
+                cp.dictionary.clone(),
+                cp.case_sensitive,
+            ),
+            cp.contract_nonce
+                .map_or(SearchMode::Eoa, |nonce| SearchMode::Create { nonce }),
+            cp.count,
+            cp.rule.clone(),
+            cp.seed,
+        ),
+        None => {
+            let criteria = Criteria::from_args(args);
+            let mode = search_mode(args);
+            // Create a rule string for the filename
+            let rule = match mode {
+                SearchMode::Create { nonce } => {
+                    format!("create_nonce{nonce}_{}", criteria.rule_name())
+                }
+                SearchMode::Eoa => criteria.rule_name(),
+            };
+            (criteria, mode, args.count, rule, args.seed)
         }
-    } else {
-        None
     };
+    let case_sensitive = criteria.rule.is_case_sensitive();
 
-    // Generate the output filename for JSON results
-    let filename = generate_filename(&args.output_dir, &rule);
+    // Print information about the search
+    criteria.print_info(count);
+    if case_sensitive {
+        say!("Case-sensitive matching against the EIP-55 checksum is enabled");
+    }
+    if let SearchMode::Create { nonce } = mode {
+        say!("Matching CREATE contract addresses deployed at nonce {nonce}");
+    }
+    warn_if_seeded(seed);
 
-    // Initialize results based on whether keystore mode is enabled
-    let timestamp = Local::now().to_rfc3339();
+    let mut output = match &checkpoint {
+        Some(cp) => Output::resume(cp, args.identity.as_deref()),
+        None => Output::new(args, &rule),
+    };
+    let prior = match &checkpoint {
+        Some(cp) => SearchProgress {
+            found: output.found_count(),
+            checked: cp.checked,
+            elapsed: Duration::from_secs_f64(cp.elapsed_secs),
+        },
+        None => SearchProgress::default(),
+    };
+    // Results piped to stdout never touch the disk, so neither does a
+    // checkpoint holding them; such a search can't be resumed.
+    let checkpoint_file = match (&args.resume, output.filename.as_str()) {
+        (Some(path), _) => Some(path.clone()),
+        (None, STDOUT) => None,
+        (None, filename) => Some(checkpoint_filename(filename)),
+    };
+    match &checkpoint_file {
+        Some(checkpoint_file) => say!("Checkpointing progress to {checkpoint_file}"),
+        None => say!(
+            "Results only go to stdout; no checkpoint is written and the search can't be resumed"
+        ),
+    }
 
-    let mut standard_results = if !args.keystore {
-        Some(Results {
-            timestamp: timestamp.clone(),
+    let contract_nonce = match mode {
+        SearchMode::Create { nonce } => Some(nonce),
+        SearchMode::Eoa => None,
+    };
+    let write_checkpoint = |output: &Output, progress: SearchProgress| {
+        let Some(checkpoint_file) = &checkpoint_file else {
+            return;
+        };
+        let checkpoint = Checkpoint {
+            timestamp: Local::now().to_rfc3339(),
             rule: rule.clone(),
-            key_pairs: Vec::new(),
-        })
-    } else {
-        None
+            prefix: criteria.prefix.clone(),
+            suffix: criteria.suffix.clone(),
+            pattern: criteria.pattern.clone(),
+            dictionary: criteria.dictionary.clone(),
+            case_sensitive,
+            contract_nonce,
+            seed,
+            count,
+            checked: progress.checked,
+            elapsed_secs: progress.elapsed.as_secs_f64(),
+            completed: output.found_count() >= count,
+            results_file: output.filename.clone(),
+            format: output.format,
+            encrypted: output.encrypted,
+            encrypted_to: output.encrypted_to.clone(),
+            keystore_dir: output.keystore_dir.clone(),
+            results: output
+                .standard_results
+                .clone()
+                .filter(|_| !output.encrypted),
+            keystore_results: output
+                .keystore_results
+                .clone()
+                .filter(|_| !output.encrypted),
+        };
+        if let Err(e) = save_checkpoint(checkpoint_file, &checkpoint) {
+            eprintln!("{e}");
+        }
     };
 
-    let mut keystore_results = if args.keystore {
-        Some(KeystoreResults {
-            timestamp,
-            rule: rule.clone(),
-            addresses: Vec::new(),
-        })
-    } else {
-        None
+    // Write the (possibly empty) results file and an initial checkpoint up
+    // front so the job can be resumed even if it dies before the first match.
+    output.save();
+    write_checkpoint(&output, prior);
+    let recorder = Recorder {
+        stdout: StdoutObserver::new(count).with_difficulty(criteria.difficulty()),
+        criteria: &criteria,
+        output: Mutex::new(output),
+        write_checkpoint,
+        last_checkpoint: Mutex::new(Instant::now()),
     };
+    let mut search = Search::new(criteria.rule.clone())
+        .count(count)
+        .threads(args.threads)
+        .mode(mode)
+        .resume_from(prior)
+        .cancel_flag(interrupt_flag());
+    if let Some(seed) = seed {
+        search = search.seed(seed);
+    }
 
     // Find matching addresses in parallel
-    println!(
+    say!(
         "Searching for addresses with {} CPU threads...",
         args.threads
     );
-    let found_addresses = find_addresses_parallel(args.count, &prefix, &suffix, args.threads);
+    say!(
+        "Using {} CPU threads for parallel processing",
+        search.thread_count()
+    );
+    let outcome = search.run(&recorder);
 
-    // Process the found addresses
-    for found in found_addresses {
-        let private_key = found.private_key;
-        let address = found.address;
-        let private_key_hex = hex::encode(private_key.secret_bytes());
+    // Every match and the final checkpoint are already on disk.
+    if let SearchStatus::Cancelled { found, requested } = outcome.status {
+        let output = recorder.output.lock().unwrap();
+        match &checkpoint_file {
+            Some(checkpoint_file) => say!(
+                "Saved {found} of {requested} addresses to {}; continue with --resume {checkpoint_file}",
+                output.sink.location()
+            ),
+            None => say!(
+                "Wrote {found} of {requested} addresses to {}",
+                output.sink.location()
+            ),
+        }
+        process::exit(130);
+    }
+}
 
-        // If not in keystore mode, add private key to results
-        if let Some(results) = &mut standard_results {
-            results.key_pairs.push(KeyPair {
-                private_key: private_key_hex.clone(),
-                public_address: address.clone(),
-            });
+/// `--seed` makes every key recomputable, so say so loudly before searching.
+fn warn_if_seeded(seed: Option<u64>) {
+    if let Some(seed) = seed {
+        eprintln!(
+            "WARNING: keys are derived from --seed {seed} and can be recomputed by anyone who knows it. Use them for testing only and never fund these addresses."
+        );
+    }
+}
+
+/// Search observer for the CLI: prints like [`StdoutObserver`] and persists
+/// every match, with a checkpoint after each match, every
+/// [`CHECKPOINT_INTERVAL`] and when the search stops. Mnemonic searches
+/// aren't resumable and pass a `write_checkpoint` that does nothing.
+struct Recorder<'a, C> {
+    stdout: StdoutObserver,
+    criteria: &'a Criteria,
+    output: Mutex<Output>,
+    write_checkpoint: C,
+    last_checkpoint: Mutex<Instant>,
+}
+
+impl<C> Recorder<'_, C>
+where
+    C: Fn(&Output, SearchProgress),
+{
+    /// Record and save a match with `notes`, then checkpoint.
+    fn save(&self, found: &FoundAddress, mut notes: KeyNotes, progress: SearchProgress) {
+        notes.word = self.criteria.word_for(found);
+        if let Some(word) = &notes.word {
+            say!("  Spells: {word}");
         }
+        let mut output = self.output.lock().unwrap();
+        output.record(found.clone(), notes);
+        output.save();
+        (self.write_checkpoint)(&output, progress);
+    }
+}
 
-        // Generate keystore file if requested
-        if args.keystore {
-            if let (Some(dir), Some(pwd)) = (&keystore_dir, &password) {
-                // Convert hex private key back to bytes
-                let private_key_bytes = match hex::decode(&private_key_hex) {
-                    Ok(bytes) => bytes,
-                    Err(e) => {
-                        eprintln!("Error decoding private key: {e}");
-                        process::exit(1);
-                    }
-                };
-
-                // Generate keystore file
-                match generate_keystore(&private_key_bytes, pwd, Path::new(dir), &address) {
-                    Ok(path) => {
-                        println!("Keystore file created: {}", path.display());
-
-                        // Add to keystore results
-                        if let Some(keystore_results) = &mut keystore_results {
-                            keystore_results.addresses.push(PublicAddressEntry {
-                                public_address: address.clone(),
-                                keystore_file: path.display().to_string(),
-                            });
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("Error creating keystore file: {e}");
-                        process::exit(1);
+impl<C> SearchObserver for Recorder<'_, C>
+where
+    C: Fn(&Output, SearchProgress) + Sync,
+{
+    fn on_match(&self, found: &FoundAddress, progress: SearchProgress) {
+        self.stdout.on_match(found, progress);
+        self.save(found, KeyNotes::default(), progress);
+    }
+
+    fn on_mnemonic_match(&self, found: &FoundMnemonic, progress: SearchProgress) {
+        self.stdout.on_mnemonic_match(found, progress);
+        let notes = KeyNotes {
+            mnemonic: Some(found.mnemonic.clone()),
+            derivation_path: Some(found.derivation_path.clone()),
+            ..KeyNotes::default()
+        };
+        self.save(&found.found, notes, progress);
+    }
+
+    fn on_progress(&self, progress: SearchProgress, rate: f64) {
+        self.stdout.on_progress(progress, rate);
+        let mut last_checkpoint = self.last_checkpoint.lock().unwrap();
+        if last_checkpoint.elapsed() >= CHECKPOINT_INTERVAL {
+            (self.write_checkpoint)(&self.output.lock().unwrap(), progress);
+            *last_checkpoint = Instant::now();
+        }
+    }
+
+    fn on_finish(&self, outcome: &SearchOutcome, progress: SearchProgress) {
+        (self.write_checkpoint)(&self.output.lock().unwrap(), progress);
+        self.stdout.on_finish(outcome, progress);
+    }
+}
+
+/// Keep the address with the most zero bytes, saving every improvement as it
+/// is found so an interrupted run still leaves the best-so-far on disk.
+fn run_zeros(args: &Args, zeros: &ZerosArgs) {
+    if args.prefix.is_some()
+        || args.suffix.is_some()
+        || args.prefix_file.is_some()
+        || args.suffix_file.is_some()
+        || args.words.is_some()
+        || args.pattern.is_some()
+    {
+        eprintln!("--prefix, --suffix, --words and --pattern do not apply to zero-byte mining");
+        process::exit(1);
+    }
+
+    let metric = if zeros.total {
+        ZeroMetric::Total
+    } else {
+        ZeroMetric::Leading
+    };
+    let mode = search_mode(args);
+
+    let metric_name = match metric {
+        ZeroMetric::Leading => "leading",
+        ZeroMetric::Total => "total",
+    };
+    let mut rule = format!("zeros_{metric_name}");
+    if let Some(target) = zeros.target {
+        rule.push_str(&format!("_target{target}"));
+    }
+    if let SearchMode::Create { nonce } = mode {
+        rule = format!("create_nonce{nonce}_{rule}");
+    }
+
+    say!("Ethereum Address Finder");
+    say!("Maximizing {metric_name} zero bytes");
+    if let Some(target) = zeros.target {
+        say!("  Stop at: {target} zero bytes");
+    }
+    if let Some(secs) = zeros.time_budget {
+        say!("  Time budget: {secs}s");
+    }
+    if let SearchMode::Create { nonce } = mode {
+        say!("Scoring CREATE contract addresses deployed at nonce {nonce}");
+    }
+    warn_if_seeded(args.seed);
+
+    let output = Mutex::new(Output::new(args, &rule));
+    let cancel = interrupt_flag();
+
+    say!(
+        "Searching for addresses with {} CPU threads...",
+        args.threads
+    );
+    let stop = ScoreStop {
+        target: zeros.target,
+        time_budget: zeros.time_budget.map(Duration::from_secs),
+    };
+    find_best_address_parallel(
+        ZeroByteScorer::new(metric),
+        args.threads,
+        mode,
+        stop,
+        args.seed,
+        &cancel,
+        |scored| {
+            let mut output = output.lock().unwrap();
+            output.record(scored.found.clone(), KeyNotes::default());
+            output.save();
+        },
+    );
+    exit_if_cancelled(&cancel);
+}
+
+/// Mine CREATE2 salts; no private keys are produced, so there is no keystore
+/// handling and results always go to a plain JSON file.
+fn run_create2(args: &Args, create2: &Create2Args) {
+    if args.keystore || args.contract || args.seed.is_some() {
+        eprintln!("--keystore, --contract and --seed do not apply to create2 salt mining");
+        process::exit(1);
+    }
+
+    let criteria = Criteria::from_args(args);
+    let rule = format!("create2_{}", criteria.rule_name());
+
+    let init_code_hash = match (&create2.init_code_hash, &create2.init_code) {
+        (Some(hash), _) => *hash,
+        (None, Some(code)) => keccak256(&hex::decode(code).expect("validated by clap")),
+        (None, None) => unreachable!("clap requires --init-code-hash or --init-code"),
+    };
+
+    criteria.print_info(args.count);
+    say!("  Deployer: {}", address_to_hex(&create2.deployer));
+    say!("  Init code hash: 0x{}", hex::encode(init_code_hash));
+
+    if let Err(e) = ensure_output_dir(&args.output_dir) {
+        eprintln!("Error creating output directory: {e}");
+        process::exit(1);
+    }
+    let filename = generate_filename(&args.output_dir, &rule, "json");
+
+    // Write the (empty) results file up front, refusing to clobber one that
+    // exists; each salt then replaces it as it is found.
+    let results = Create2Results {
+        timestamp: Local::now().to_rfc3339(),
+        rule,
+        deployer: address_to_hex(&create2.deployer),
+        init_code_hash: format!("0x{}", hex::encode(init_code_hash)),
+        salts: Vec::new(),
+    };
+    if let Err(e) = save_create2_results(&filename, &results, false) {
+        eprintln!("{e}");
+        process::exit(1);
+    }
+    let recorder = Create2Recorder {
+        stdout: StdoutObserver::new(args.count).with_difficulty(criteria.difficulty()),
+        filename,
+        results: Mutex::new(results),
+    };
+
+    let cancel = interrupt_flag();
+    let search = Search::new(criteria.rule.clone())
+        .count(args.count)
+        .threads(args.threads)
+        .create2(create2.deployer, init_code_hash)
+        .cancel_flag(Arc::clone(&cancel));
+    say!(
+        "Mining CREATE2 salts with {} CPU threads...",
+        search.thread_count()
+    );
+    search.run(&recorder);
+    exit_if_cancelled(&cancel);
+}
+
+/// Search observer for `create2`: prints like [`StdoutObserver`] and saves
+/// the results file after every salt, so an interrupted run keeps them.
+struct Create2Recorder {
+    stdout: StdoutObserver,
+    filename: String,
+    results: Mutex<Create2Results>,
+}
+
+impl SearchObserver for Create2Recorder {
+    fn on_salt_match(&self, found: &FoundSalt, progress: SearchProgress) {
+        self.stdout.on_salt_match(found, progress);
+        let mut results = self.results.lock().unwrap();
+        results.timestamp = Local::now().to_rfc3339();
+        results.salts.push(SaltEntry {
+            salt: format!("0x{}", hex::encode(found.salt)),
+            contract_address: found.address.clone(),
+        });
+        if let Err(e) = save_create2_results(&self.filename, &results, true) {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+    }
+
+    fn on_progress(&self, progress: SearchProgress, rate: f64) {
+        self.stdout.on_progress(progress, rate);
+    }
+
+    fn on_finish(&self, outcome: &SearchOutcome, progress: SearchProgress) {
+        self.stdout.on_finish(outcome, progress);
+    }
+}
+
+/// Find keys derived from fresh BIP-39 mnemonics, saving each with its
+/// mnemonic (or, in keystore mode, only its derivation path) as it is found.
+fn run_mnemonic(args: &Args, mnemonic: &MnemonicArgs) {
+    if args.seed.is_some() {
+        eprintln!("--seed does not apply to mnemonic search; mnemonics always use fresh entropy");
+        process::exit(1);
+    }
+
+    let criteria = Criteria::from_args(args);
+    let mode = search_mode(args);
+    let rule = match mode {
+        SearchMode::Create { nonce } => {
+            format!("mnemonic_create_nonce{nonce}_{}", criteria.rule_name())
+        }
+        SearchMode::Eoa => format!("mnemonic_{}", criteria.rule_name()),
+    };
+
+    criteria.print_info(args.count);
+    if mnemonic.indexes == 1 {
+        say!(
+            "  Mnemonics: {} words, first account ({ETH_ACCOUNT_PATH}/0) only",
+            mnemonic.mnemonic_words
+        );
+    } else {
+        say!(
+            "  Mnemonics: {} words, accounts {ETH_ACCOUNT_PATH}/0 to /{}",
+            mnemonic.mnemonic_words,
+            mnemonic.indexes - 1
+        );
+    }
+    if let SearchMode::Create { nonce } = mode {
+        say!("Matching CREATE contract addresses deployed at nonce {nonce}");
+    }
+
+    let mnemonics = match MnemonicSearch::new(mnemonic.mnemonic_words, mnemonic.indexes) {
+        Ok(mnemonics) => mnemonics,
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+    };
+    let mut output = Output::new(args, &rule);
+    output.save();
+    let recorder = Recorder {
+        stdout: StdoutObserver::new(args.count).with_difficulty(criteria.difficulty()),
+        criteria: &criteria,
+        output: Mutex::new(output),
+        write_checkpoint: |_: &Output, _: SearchProgress| {},
+        last_checkpoint: Mutex::new(Instant::now()),
+    };
+    let cancel = interrupt_flag();
+    let search = Search::new(criteria.rule.clone())
+        .count(args.count)
+        .threads(args.threads)
+        .mode(mode)
+        .mnemonics(mnemonics)
+        .cancel_flag(Arc::clone(&cancel));
+
+    say!("Searching mnemonics with {} CPU threads...", args.threads);
+    say!(
+        "Using {} CPU threads for parallel processing",
+        search.thread_count()
+    );
+    search.run(&recorder);
+    exit_if_cancelled(&cancel);
+}
+
+/// Search for partial keys on behalf of `split.public_key`. Only the partial
+/// keys are written; the requester adds their private key with `combine`.
+fn run_split(args: &Args, split: &SplitArgs) {
+    if args.keystore {
+        eprintln!("--keystore does not apply to split; no private key is ever produced here");
+        process::exit(1);
+    }
+
+    let criteria = Criteria::from_args(args);
+    let mode = search_mode(args);
+    let rule = match mode {
+        SearchMode::Create { nonce } => {
+            format!("split_create_nonce{nonce}_{}", criteria.rule_name())
+        }
+        SearchMode::Eoa => format!("split_{}", criteria.rule_name()),
+    };
+    let public_key = format!(
+        "0x{}",
+        hex::encode(split.public_key.serialize_uncompressed())
+    );
+
+    criteria.print_info(args.count);
+    say!("  Public key: {public_key}");
+    if let SearchMode::Create { nonce } = mode {
+        say!("Matching CREATE contract addresses deployed at nonce {nonce}");
+    }
+    warn_if_seeded(args.seed);
+
+    if let Err(e) = ensure_output_dir(&args.output_dir) {
+        eprintln!("Error creating output directory: {e}");
+        process::exit(1);
+    }
+    let filename = generate_filename(&args.output_dir, &rule, "json");
+
+    // Write the (empty) results file up front, refusing to clobber one that
+    // exists; each partial key then replaces it as it is found.
+    let results = SplitResults {
+        timestamp: Local::now().to_rfc3339(),
+        rule,
+        public_key,
+        contract_nonce: args.contract.then_some(args.nonce),
+        partial_keys: Vec::new(),
+    };
+    if let Err(e) = save_split_results(&filename, &results, false) {
+        eprintln!("{e}");
+        process::exit(1);
+    }
+    let recorder = SplitRecorder {
+        stdout: StdoutObserver::new(args.count).with_difficulty(criteria.difficulty()),
+        criteria: &criteria,
+        filename,
+        results: Mutex::new(results),
+    };
+
+    let cancel = interrupt_flag();
+    let mut search = Search::new(criteria.rule.clone())
+        .count(args.count)
+        .threads(args.threads)
+        .mode(mode)
+        .split_key(split.public_key)
+        .cancel_flag(Arc::clone(&cancel));
+    if let Some(seed) = args.seed {
+        search = search.seed(seed);
+    }
+    say!(
+        "Searching for partial keys with {} CPU threads...",
+        search.thread_count()
+    );
+    search.run(&recorder);
+    exit_if_cancelled(&cancel);
+}
+
+/// Search observer for `split`: prints like [`StdoutObserver`] and saves the
+/// results file after every partial key, so an interrupted run keeps them.
+struct SplitRecorder<'a> {
+    stdout: StdoutObserver,
+    criteria: &'a Criteria,
+    filename: String,
+    results: Mutex<SplitResults>,
+}
+
+impl SearchObserver for SplitRecorder<'_> {
+    fn on_match(&self, found: &FoundAddress, progress: SearchProgress) {
+        self.stdout.on_match(found, progress);
+        let mut results = self.results.lock().unwrap();
+        results.timestamp = Local::now().to_rfc3339();
+        results.partial_keys.push(PartialKeyEntry {
+            word: self.criteria.word_for(found),
+            partial_key: hex::encode(found.private_key.secret_bytes()),
+            public_address: found.address.clone(),
+            contract_address: found.contract_address.clone(),
+        });
+        if let Err(e) = save_split_results(&self.filename, &results, true) {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+    }
+
+    fn on_progress(&self, progress: SearchProgress, rate: f64) {
+        self.stdout.on_progress(progress, rate);
+    }
+
+    fn on_finish(&self, outcome: &SearchOutcome, progress: SearchProgress) {
+        self.stdout.on_finish(outcome, progress);
+    }
+}
+
+/// Turn the partial keys of a `split` results file into private keys,
+/// checking each against the address the searcher reported before saving
+/// anything.
+fn run_combine(args: &Args, combine: &CombineArgs) {
+    if args.contract {
+        eprintln!(
+            "--contract does not apply to combine; the nonce is taken from the split results"
+        );
+        process::exit(1);
+    }
+    let split = match load_split_results(&combine.results) {
+        Ok(split) => split,
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+    };
+    let public_key = match parse_public_key(&split.public_key) {
+        Ok(public_key) => public_key,
+        Err(e) => {
+            eprintln!("{}: {e}", combine.results);
+            process::exit(1);
+        }
+    };
+    let secret = match read_secret_key(combine.secret_key_file.as_deref()) {
+        Ok(secret) => secret,
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+    };
+    if private_key_to_address_bytes(&secret) != public_key_to_address_bytes(&public_key) {
+        eprintln!(
+            "The private key does not belong to the public key in {}",
+            combine.results
+        );
+        process::exit(1);
+    }
+
+    let mut found = Vec::with_capacity(split.partial_keys.len());
+    for entry in &split.partial_keys {
+        let combined = parse_secret_key(&entry.partial_key)
+            .and_then(|partial| combine_split_key(&secret, &partial));
+        let private_key = match combined {
+            Ok(private_key) => private_key,
+            Err(e) => {
+                eprintln!("{}: {e}", entry.public_address);
+                process::exit(1);
+            }
+        };
+        let address_bytes = private_key_to_address_bytes(&private_key);
+        let contract_bytes = split
+            .contract_nonce
+            .map(|nonce| contract_address_bytes(&address_bytes, nonce));
+        let matches = entry
+            .public_address
+            .eq_ignore_ascii_case(&address_to_hex(&address_bytes))
+            && entry
+                .contract_address
+                .as_ref()
+                .map(|a| a.to_ascii_lowercase())
+                == contract_bytes.map(|bytes| address_to_hex(&bytes));
+        if !matches {
+            eprintln!(
+                "Partial key for {} does not produce that address; refusing to save any keys",
+                entry.public_address
+            );
+            process::exit(1);
+        }
+        say!("Verified {}", entry.public_address);
+        found.push((
+            FoundAddress {
+                private_key,
+                address: entry.public_address.clone(),
+                contract_address: entry.contract_address.clone(),
+            },
+            entry.word.clone(),
+        ));
+    }
+
+    let rule = split.rule.strip_prefix("split_").unwrap_or(&split.rule);
+    let mut output = Output::new(args, &format!("combined_{rule}"));
+    if let Some(results) = &mut output.standard_results {
+        results.contract_nonce = split.contract_nonce;
+    }
+    if let Some(results) = &mut output.keystore_results {
+        results.contract_nonce = split.contract_nonce;
+    }
+    for (found, word) in found {
+        output.record(
+            found,
+            KeyNotes {
+                word,
+                ..KeyNotes::default()
+            },
+        );
+    }
+    output.save();
+}
+
+/// Measure keys/sec at increasing thread counts and print a scaling table
+/// (or JSON) so a `--threads` value can be picked for the machine.
+fn run_bench(args: &Args, bench: &BenchArgs) {
+    if args.seed.is_some() {
+        eprintln!("--seed does not apply to bench; throughput doesn't depend on the keys");
+        process::exit(1);
+    }
+    let has_criteria = args.prefix.is_some()
+        || args.suffix.is_some()
+        || args.prefix_file.is_some()
+        || args.suffix_file.is_some()
+        || args.words.is_some()
+        || args.pattern.is_some();
+    let (rule, rule_name) = if has_criteria {
+        let criteria = Criteria::from_args(args);
+        let name = criteria.rule_name();
+        (criteria.rule, name)
+    } else {
+        // A 16-nibble prefix never matches in practice, so every candidate
+        // takes the full reject path.
+        let rule = MatchRule::new(&["0123456789abcdef"], &[]).expect("valid prefix");
+        (rule, "reject_all".to_string())
+    };
+    let mode = search_mode(args);
+    let rule_name = match mode {
+        SearchMode::Create { nonce } => format!("create_nonce{nonce}_{rule_name}"),
+        SearchMode::Eoa => rule_name,
+    };
+    let thread_counts = bench_thread_counts(bench.max_threads);
+    let duration = Duration::from_secs(bench.duration);
+
+    let cpus = num_cpus::get();
+    if !bench.json {
+        say!("Ethereum Address Finder benchmark");
+        say!("  Rule: {rule_name}");
+        say!("  CPU cores: {cpus}");
+        say!(
+            "  {}s per thread count: {}",
+            bench.duration,
+            thread_counts
+                .iter()
+                .map(usize::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        say!();
+        say!(
+            "{:>8} {:>14} {:>14} {:>8} {:>11}",
+            "Threads",
+            "Keys/sec",
+            "Per thread",
+            "Speedup",
+            "Efficiency"
+        );
+    }
+
+    let cancel = interrupt_flag();
+    let samples = benchmark_threads(&rule, mode, &thread_counts, duration, &cancel, |s| {
+        if !bench.json {
+            say!(
+                "{:>8} {:>14.0} {:>14.0} {:>7.2}x {:>10.0}%",
+                s.threads,
+                s.keys_per_sec,
+                s.keys_per_sec_per_thread,
+                s.threads as f64 * s.efficiency,
+                s.efficiency * 100.0
+            );
+        }
+    });
+
+    let report = BenchReport {
+        timestamp: Local::now().to_rfc3339(),
+        rule: rule_name,
+        cpus,
+        duration_secs: duration.as_secs_f64(),
+        samples,
+    };
+    if bench.json {
+        match serde_json::to_string_pretty(&report) {
+            Ok(json) => say!("{json}"),
+            Err(e) => {
+                eprintln!("Error serializing benchmark results: {e}");
+                process::exit(1);
+            }
+        }
+    } else if let Some(best) = report
+        .samples
+        .iter()
+        .max_by(|a, b| a.keys_per_sec.total_cmp(&b.keys_per_sec))
+    {
+        say!();
+        say!(
+            "Fastest: --threads {} ({:.0} keys/sec)",
+            best.threads,
+            best.keys_per_sec
+        );
+    }
+    exit_if_cancelled(&cancel);
+}
+
+fn run_keystore(command: &KeystoreCommand) {
+    let password = match read_password("keystore") {
+        Ok(password) => password,
+        Err(e) => {
+            eprintln!("Error reading password: {e}");
+            process::exit(1);
+        }
+    };
+    match command {
+        KeystoreCommand::Verify { path } => verify_keystores(Path::new(path), password.expose()),
+        KeystoreCommand::Export { file } => export_keystore(Path::new(file), password.expose()),
+    }
+}
+
+/// Check every keystore file under `path`, exiting non-zero if any cannot
+/// be decrypted or names an address its key does not have.
+fn verify_keystores(path: &Path, password: &str) {
+    let files = match keystore_files(path) {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+    };
+    if files.is_empty() {
+        eprintln!("No keystore files in {}", path.display());
+        process::exit(1);
+    }
+
+    let mut failed = 0;
+    for file in &files {
+        match verify_keystore(file, password) {
+            Ok(check) => {
+                let mismatches = check.mismatches();
+                if mismatches.is_empty() {
+                    println!("OK        {} (0x{})", file.display(), check.derived);
+                } else {
+                    failed += 1;
+                    println!("MISMATCH  {}", file.display());
+                    for mismatch in mismatches {
+                        println!("  {mismatch}");
                     }
                 }
             }
+            Err(e) => {
+                failed += 1;
+                println!("ERROR     {e}");
+            }
         }
     }
 
-    // Save results to JSON file
-    if let Err(e) = save_results(&filename, &standard_results, &keystore_results) {
-        eprintln!("{e}");
+    println!(
+        "\n{} of {} keystore files verified",
+        files.len() - failed,
+        files.len()
+    );
+    if failed > 0 {
         process::exit(1);
     }
 }
+
+/// Print the plaintext private key of a keystore file once the user has
+/// confirmed it.
+fn export_keystore(file: &Path, password: &str) {
+    let secret = decrypt_keystore(file, password).and_then(|secret| {
+        let mismatches = check_keystore(file, &secret)?.mismatches();
+        if mismatches.is_empty() {
+            Ok(secret)
+        } else {
+            Err(format!("{}: {}", file.display(), mismatches.join("; ")))
+        }
+    });
+    let secret = match secret {
+        Ok(secret) => secret,
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+    };
+
+    let prompt = format!(
+        "This prints the private key of {} in plaintext to the terminal.",
+        private_key_to_address(&secret)
+    );
+    match confirm(&prompt, "export") {
+        Ok(true) => {
+            let bytes = Zeroizing::new(secret.secret_bytes());
+            println!("0x{}", SecretHex::from_bytes(&bytes).expose());
+        }
+        Ok(false) => {
+            eprintln!("Export cancelled");
+            process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Error reading confirmation: {e}");
+            process::exit(1);
+        }
+    }
+}
+
+/// Print the plaintext of a results file written with --encrypt-results, or
+/// with --encrypt-to given the `identity` file, to stdout. The password
+/// prompt goes to stderr so the output can be redirected to a file.
+fn run_decrypt_results(file: &str, identity: Option<&str>) {
+    messages_to_stderr();
+    let key = match identity {
+        Some(identity) => ResultsKey::identity_file(identity),
+        None => read_password("results")
+            .map(ResultsKey::Passphrase)
+            .map_err(|e| format!("Error reading password: {e}")),
+    };
+    match key.and_then(|key| decrypt_results_file(file, &key)) {
+        Ok(document) => print!("{}", *document),
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+    }
+}
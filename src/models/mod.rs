@@ -1,11 +1,55 @@
+use std::fmt;
+
 use secp256k1::SecretKey;
 use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
 
-/// Represents a key pair with private key and public address
-#[derive(Serialize, Deserialize, Debug, Clone)]
+use crate::dictionary::Dictionary;
+use crate::keystore::KeystoreOptions;
+use crate::utils::OutputFormat;
+
+mod secret;
+
+pub use secret::{Password, SecretHex};
+
+/// Represents a key pair with private key and public address. The key and
+/// mnemonic are wiped when it is dropped.
+#[derive(Serialize, Deserialize, Clone)]
 pub struct KeyPair {
-    pub private_key: String,
+    pub private_key: SecretHex,
     pub public_address: String,
+    /// Address of the contract `public_address` deploys with `CREATE`, when
+    /// searching in contract mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract_address: Option<String>,
+    /// Dictionary word the matched address spells, in `--words` mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub word: Option<String>,
+    /// BIP-39 mnemonic the key was derived from, in mnemonic mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mnemonic: Option<String>,
+    /// BIP-32 path of the key under `mnemonic`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub derivation_path: Option<String>,
+}
+
+impl Drop for KeyPair {
+    fn drop(&mut self) {
+        self.mnemonic.zeroize();
+    }
+}
+
+impl fmt::Debug for KeyPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyPair")
+            .field("private_key", &self.private_key)
+            .field("public_address", &self.public_address)
+            .field("contract_address", &self.contract_address)
+            .field("word", &self.word)
+            .field("mnemonic", &self.mnemonic.as_ref().map(|_| "<redacted>"))
+            .field("derivation_path", &self.derivation_path)
+            .finish()
+    }
 }
 
 /// Represents a public address entry with keystore file path
@@ -13,6 +57,17 @@ pub struct KeyPair {
 pub struct PublicAddressEntry {
     pub public_address: String,
     pub keystore_file: String,
+    /// Address of the contract `public_address` deploys with `CREATE`, when
+    /// searching in contract mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract_address: Option<String>,
+    /// Dictionary word the matched address spells, in `--words` mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub word: Option<String>,
+    /// BIP-32 path of the key in mnemonic mode. The mnemonic itself is never
+    /// written in keystore mode; the keystore holds the key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub derivation_path: Option<String>,
 }
 
 /// Represents the results with private keys
@@ -20,6 +75,9 @@ pub struct PublicAddressEntry {
 pub struct Results {
     pub timestamp: String,
     pub rule: String,
+    /// Deployer nonce used for `CREATE` contract address matching.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract_nonce: Option<u64>,
     pub key_pairs: Vec<KeyPair>,
 }
 
@@ -28,12 +86,189 @@ pub struct Results {
 pub struct KeystoreResults {
     pub timestamp: String,
     pub rule: String,
+    /// Deployer nonce used for `CREATE` contract address matching.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract_nonce: Option<u64>,
+    /// KDF and salt length the keystore files were encrypted with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<KeystoreOptions>,
     pub addresses: Vec<PublicAddressEntry>,
 }
 
-/// Structure to hold a found address and its private key
-#[derive(Debug)]
+/// Periodic snapshot of a running search, written next to the results file
+/// so `--resume` can continue the same job. It holds the same results
+/// document as the results file, so in keystore mode only the public
+/// [`KeystoreResults`] are stored and never a plaintext key, and none at all
+/// when the results file is encrypted.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Checkpoint {
+    pub timestamp: String,
+    pub rule: String,
+    pub prefix: Option<Vec<String>>,
+    pub suffix: Option<Vec<String>>,
+    pub pattern: Option<Vec<String>>,
+    /// Word list of a `--words` search, with every spelling searched for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dictionary: Option<Dictionary>,
+    pub case_sensitive: bool,
+    pub contract_nonce: Option<u64>,
+    /// Insecure `--seed` the keys are derived from, so a resumed search
+    /// stays reproducible.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    pub count: usize,
+    pub checked: u64,
+    pub elapsed_secs: f64,
+    pub completed: bool,
+    /// Results file, or `-` for stdout.
+    pub results_file: String,
+    #[serde(default)]
+    pub format: OutputFormat,
+    /// The results file is encrypted with `--encrypt-results` or
+    /// `--encrypt-to`. The results are then left out of the checkpoint and
+    /// read back from the file.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub encrypted: bool,
+    /// age recipients (`age1…`) of an `--encrypt-to` job; empty when the
+    /// results are encrypted to a passphrase.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub encrypted_to: Vec<String>,
+    pub keystore_dir: Option<String>,
+    pub results: Option<Results>,
+    pub keystore_results: Option<KeystoreResults>,
+}
+
+/// Represents a mined CREATE2 salt and the contract address it produces
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SaltEntry {
+    pub salt: String,
+    pub contract_address: String,
+}
+
+/// Represents the results of a CREATE2 salt search (no private keys involved)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Create2Results {
+    pub timestamp: String,
+    pub rule: String,
+    pub deployer: String,
+    pub init_code_hash: String,
+    pub salts: Vec<SaltEntry>,
+}
+
+/// A partial key found by the `split` subcommand. Added to the secret key of
+/// the search's public key, it gives the private key of `public_address`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PartialKeyEntry {
+    pub partial_key: String,
+    pub public_address: String,
+    /// Address of the contract `public_address` deploys with `CREATE`, when
+    /// searching in contract mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract_address: Option<String>,
+    /// Dictionary word the matched address spells, in `--words` mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub word: Option<String>,
+}
+
+/// Represents the results of a split-key search. The partial keys are
+/// useless without the secret key of `public_key`, so this file can safely
+/// come back from an untrusted machine.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SplitResults {
+    pub timestamp: String,
+    pub rule: String,
+    /// The requester's public key, uncompressed hex.
+    pub public_key: String,
+    /// Deployer nonce used for `CREATE` contract address matching.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract_nonce: Option<u64>,
+    pub partial_keys: Vec<PartialKeyEntry>,
+}
+
+/// Throughput measured at one thread count by the `bench` subcommand
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BenchSample {
+    pub threads: usize,
+    pub checked: u64,
+    pub elapsed_secs: f64,
+    pub keys_per_sec: f64,
+    pub keys_per_sec_per_thread: f64,
+    /// Per-thread throughput relative to the single-thread run
+    pub efficiency: f64,
+}
+
+/// Represents the output of the `bench` subcommand
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BenchReport {
+    pub timestamp: String,
+    pub rule: String,
+    pub cpus: usize,
+    pub duration_secs: f64,
+    pub samples: Vec<BenchSample>,
+}
+
+/// Structure to hold a found address and its private key, which is erased
+/// when it is dropped. `SecretKey` is `Copy`, so this only wipes the copy
+/// held here, not ones made while deriving or serializing the key.
+#[derive(Clone)]
 pub struct FoundAddress {
     pub private_key: SecretKey,
     pub address: String,
+    /// The matched `CREATE` contract address, if searching in contract mode.
+    pub contract_address: Option<String>,
+}
+
+impl Drop for FoundAddress {
+    fn drop(&mut self) {
+        self.private_key.non_secure_erase();
+    }
+}
+
+impl fmt::Debug for FoundAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FoundAddress")
+            .field("private_key", &"<redacted>")
+            .field("address", &self.address)
+            .field("contract_address", &self.contract_address)
+            .finish()
+    }
+}
+
+/// Structure to hold a key found by a mnemonic search and how to recover it.
+/// The mnemonic is wiped when it is dropped.
+#[derive(Clone)]
+pub struct FoundMnemonic {
+    pub found: FoundAddress,
+    pub mnemonic: String,
+    pub derivation_path: String,
+}
+
+impl Drop for FoundMnemonic {
+    fn drop(&mut self) {
+        self.mnemonic.zeroize();
+    }
+}
+
+impl fmt::Debug for FoundMnemonic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FoundMnemonic")
+            .field("found", &self.found)
+            .field("mnemonic", &"<redacted>")
+            .field("derivation_path", &self.derivation_path)
+            .finish()
+    }
+}
+
+/// Structure to hold the best-scoring address of a zero-byte search
+#[derive(Debug, Clone)]
+pub struct ScoredAddress {
+    pub found: FoundAddress,
+    pub score: u32,
+}
+
+/// Structure to hold a found CREATE2 salt and the resulting address
+#[derive(Debug)]
+pub struct FoundSalt {
+    pub salt: [u8; 32],
+    pub address: String,
 }
@@ -0,0 +1,116 @@
+//! Wrappers for secrets that are wiped from memory when dropped and never
+//! shown by `Debug`.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+/// A private key as lowercase hex without `0x`, as results files store it.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct SecretHex(String);
+
+impl SecretHex {
+    /// Hex-encode a 32-byte private key.
+    pub fn from_bytes(bytes: &[u8; 32]) -> Self {
+        Self(hex::encode(bytes))
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for SecretHex {
+    fn from(hex: String) -> Self {
+        Self(hex)
+    }
+}
+
+impl Drop for SecretHex {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for SecretHex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretHex(<redacted>)")
+    }
+}
+
+/// A password read from the terminal.
+pub struct Password(String);
+
+impl Password {
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for Password {
+    fn from(password: String) -> Self {
+        Self(password)
+    }
+}
+
+impl Drop for Password {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for Password {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Password(<redacted>)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_are_redacted_but_serialize_as_plain_strings() {
+        let key = SecretHex::from_bytes(&[0xab; 32]);
+        assert_eq!(key.expose(), "ab".repeat(32));
+        assert_eq!(format!("{key:?}"), "SecretHex(<redacted>)");
+        assert_eq!(
+            serde_json::to_string(&key).unwrap(),
+            format!("\"{}\"", "ab".repeat(32))
+        );
+        let parsed: SecretHex = serde_json::from_str("\"cd\"").unwrap();
+        assert_eq!(parsed.expose(), "cd");
+
+        let password = Password::from("hunter2".to_string());
+        assert!(!format!("{password:?}").contains("hunter2"));
+    }
+
+    #[test]
+    fn found_keys_never_show_their_secrets_in_debug() {
+        let secret = secp256k1::SecretKey::from_slice(&[0x11; 32]).unwrap();
+        let found = crate::models::FoundMnemonic {
+            found: crate::models::FoundAddress {
+                private_key: secret,
+                address: "0xabc".to_string(),
+                contract_address: None,
+            },
+            mnemonic: "test test junk".to_string(),
+            derivation_path: "m/44'/60'/0'/0/0".to_string(),
+        };
+        let key_pair = crate::models::KeyPair {
+            private_key: SecretHex::from_bytes(&secret.secret_bytes()),
+            public_address: "0xabc".to_string(),
+            contract_address: None,
+            word: None,
+            mnemonic: Some(found.mnemonic.clone()),
+            derivation_path: None,
+        };
+        for debug in [format!("{found:?}"), format!("{key_pair:?}")] {
+            assert!(debug.contains("0xabc"), "{debug}");
+            assert!(!debug.contains("1111"), "{debug}");
+            assert!(!debug.contains("junk"), "{debug}");
+        }
+    }
+}
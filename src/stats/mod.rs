@@ -0,0 +1,200 @@
+//! How hard a search is, and how long it is likely to take.
+//!
+//! Matches arrive as a Poisson process: with per-candidate match probability
+//! `p`, the number of matches in `n` candidates is approximately
+//! Poisson(`n * p`). Completion estimates for `k` matches are quantiles of
+//! that process, and the "luck" indicator is where the observed match count
+//! falls in its distribution.
+
+use std::time::Duration;
+
+use crate::crypto::MatchRule;
+
+/// Confidence levels shown alongside the expected time.
+pub const CONFIDENCE_LEVELS: [f64; 3] = [0.5, 0.9, 0.99];
+
+/// Per-candidate match probability of a search.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Difficulty {
+    probability: f64,
+}
+
+impl Difficulty {
+    pub fn new(probability: f64) -> Self {
+        Self { probability }
+    }
+
+    pub fn from_rule(rule: &MatchRule) -> Self {
+        Self::new(rule.match_probability())
+    }
+
+    pub fn probability(&self) -> f64 {
+        self.probability
+    }
+
+    /// Mean number of candidates needed for `count` matches.
+    pub fn expected_attempts(&self, count: usize) -> f64 {
+        count as f64 / self.probability
+    }
+
+    /// Number of candidates after which `count` matches have been found with
+    /// probability `confidence` (e.g. 0.9).
+    pub fn attempts_for(&self, count: usize, confidence: f64) -> f64 {
+        if count == 0 {
+            return 0.0;
+        }
+        // Find the Poisson mean at which P(X >= count) = confidence; the tail
+        // grows monotonically with the mean, so bisect.
+        let mut lo = 0.0;
+        let mut hi = count as f64 + 20.0 * (count as f64).sqrt() + 50.0;
+        while poisson_at_least(hi, count) < confidence {
+            hi *= 2.0;
+        }
+        for _ in 0..64 {
+            let mid = (lo + hi) / 2.0;
+            if poisson_at_least(mid, count) < confidence {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        hi / self.probability
+    }
+
+    /// Where `found` matches after `checked` candidates falls among all
+    /// searches of the same length, from 0.0 (unluckiest) to 1.0 (luckiest).
+    /// 0.5 is exactly average; it starts there and drifts down while nothing
+    /// is found.
+    pub fn luck(&self, checked: u64, found: usize) -> f64 {
+        // Mid-p percentile: P(X < found) + P(X = found) / 2.
+        let mean = checked as f64 * self.probability;
+        let below = 1.0 - poisson_at_least(mean, found);
+        let at_most = 1.0 - poisson_at_least(mean, found + 1);
+        (below + at_most) / 2.0
+    }
+}
+
+/// P(X >= k) for X ~ Poisson(mean).
+fn poisson_at_least(mean: f64, k: usize) -> f64 {
+    if k == 0 {
+        return 1.0;
+    }
+    if mean <= 0.0 {
+        return 0.0;
+    }
+    // Sum P(X = i) for i < k in log space, so a large mean does not
+    // underflow the first terms.
+    let ln_mean = mean.ln();
+    let mut ln_term = -mean;
+    let mut below = ln_term.exp();
+    for i in 1..k {
+        ln_term += ln_mean - (i as f64).ln();
+        below += ln_term.exp();
+    }
+    (1.0 - below).clamp(0.0, 1.0)
+}
+
+/// Time to check `attempts` candidates at `rate` per second, if finite.
+pub fn time_for(attempts: f64, rate: f64) -> Option<Duration> {
+    if rate <= 0.0 {
+        return None;
+    }
+    Duration::try_from_secs_f64(attempts / rate).ok()
+}
+
+/// Human-readable estimate that stays short for very long times.
+pub fn format_estimate(duration: Option<Duration>) -> String {
+    const DAY: u64 = 24 * 3600;
+    const YEAR: u64 = 365 * DAY;
+    let Some(duration) = duration else {
+        return "forever".to_string();
+    };
+    let secs = duration.as_secs();
+    if secs < 1 {
+        "<1s".to_string()
+    } else if secs < 60 {
+        format!("{secs}s")
+    } else if secs < 3600 {
+        format!("{}m {}s", secs / 60, secs % 60)
+    } else if secs < DAY {
+        format!("{}h {}m", secs / 3600, (secs % 3600) / 60)
+    } else if secs < YEAR {
+        format!("{}d {}h", secs / DAY, (secs % DAY) / 3600)
+    } else {
+        format!("{} years", format_count(secs as f64 / YEAR as f64))
+    }
+}
+
+/// Exact below ten million, scientific notation above.
+pub fn format_count(n: f64) -> String {
+    if n < 1e7 {
+        format!("{n:.0}")
+    } else {
+        format!("{n:.2e}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() <= 1e-6 * b.abs().max(1.0)
+    }
+
+    #[test]
+    fn single_match_quantiles_are_geometric() {
+        let d = Difficulty::new(1.0 / 65536.0);
+        assert_eq!(d.expected_attempts(1), 65536.0);
+        // For one match, P(done by n) = 1 - e^(-np).
+        for confidence in CONFIDENCE_LEVELS {
+            let want = -(1.0 - confidence).ln() * 65536.0;
+            assert!(close(d.attempts_for(1, confidence), want));
+        }
+        assert_eq!(d.attempts_for(0, 0.9), 0.0);
+    }
+
+    #[test]
+    fn more_matches_take_proportionally_longer() {
+        let d = Difficulty::new(0.001);
+        assert_eq!(d.expected_attempts(10), 10_000.0);
+        let median = d.attempts_for(10, 0.5);
+        assert!(median > 9_000.0 && median < 10_000.0, "{median}");
+        assert!(d.attempts_for(10, 0.99) > d.attempts_for(10, 0.9));
+        // Large counts stay accurate where the first terms would underflow.
+        let big = d.attempts_for(2_000, 0.5);
+        assert!(big > 1_990_000.0 && big < 2_000_000.0, "{big}");
+    }
+
+    #[test]
+    fn luck_starts_average_and_tracks_results() {
+        let d = Difficulty::new(0.01);
+        assert_eq!(d.luck(0, 0), 0.5);
+        // Nothing after 5x the expected work is very unlucky ...
+        assert!(d.luck(500, 0) < 0.01);
+        // ... and three matches after a tenth of it very lucky.
+        assert!(d.luck(10, 3) > 0.99);
+        assert!(close(
+            d.luck(100, 1),
+            0.5 * (-1f64).exp() + 0.5 * 2.0 * (-1f64).exp()
+        ));
+    }
+
+    #[test]
+    fn estimates_format_compactly() {
+        assert_eq!(time_for(100.0, 0.0), None);
+        assert_eq!(time_for(100.0, 10.0), Some(Duration::from_secs(10)));
+        assert_eq!(format_estimate(None), "forever");
+        assert_eq!(format_estimate(Some(Duration::from_millis(10))), "<1s");
+        assert_eq!(format_estimate(Some(Duration::from_secs(3725))), "1h 2m");
+        assert_eq!(format_estimate(Some(Duration::from_secs(90_000))), "1d 1h");
+        assert_eq!(
+            format_estimate(time_for(1e20, 1e6)),
+            format!("{} years", format_count(1e14 / (365.0 * 86400.0)))
+        );
+        // Beyond what a Duration can hold.
+        assert_eq!(format_estimate(time_for(1e40, 1.0)), "forever");
+        assert_eq!(format_count(12_345.4), "12345");
+        assert_eq!(format_count(4.294967296e9), "4.29e9");
+    }
+}
@@ -0,0 +1,223 @@
+//! Encryption of the results document, for `--encrypt-results` and
+//! `--encrypt-to`.
+//!
+//! Results files are [age](https://age-encryption.org) v1 files, so `age -d`
+//! reads them as well as `decrypt-results` does. A passphrase is stretched
+//! with scrypt into a `-> scrypt` stanza; each X25519 recipient (an `age1…`
+//! public key) gets a `-> X25519 <ephemeral share>` stanza, opened with the
+//! matching `AGE-SECRET-KEY-1…` identity file. The payload is encrypted and
+//! authenticated with ChaCha20-Poly1305, so a wrong passphrase or identity,
+//! or a tampered file, is rejected.
+
+use std::io::{Read, Write};
+use std::iter;
+
+use age::secrecy::SecretString;
+use age::DecryptError;
+use zeroize::Zeroizing;
+
+use crate::models::Password;
+
+/// scrypt work factor (log2 N) for new results files, as age uses.
+pub const RESULTS_SCRYPT_LOG_N: u8 = 18;
+
+/// Largest work factor accepted when decrypting, so a crafted file can't
+/// make us allocate gigabytes.
+const MAX_SCRYPT_LOG_N: u8 = 22;
+
+const AGE_MAGIC: &[u8] = b"age-encryption.org/v1\n";
+
+/// Who a results file is encrypted to. Each save encrypts the document under
+/// a fresh file key.
+pub enum ResultsCipher {
+    /// A passphrase. scrypt runs again on every save, so a save takes
+    /// about as long as opening the file does.
+    Passphrase(age::scrypt::Recipient),
+    /// One or more X25519 public keys.
+    Recipients(Vec<age::x25519::Recipient>),
+}
+
+impl ResultsCipher {
+    /// Encrypt to `passphrase`, stretched with scrypt at work factor `log_n`.
+    pub fn passphrase(passphrase: &str, log_n: u8) -> Self {
+        let mut recipient = age::scrypt::Recipient::new(SecretString::from(passphrase.to_string()));
+        recipient.set_work_factor(log_n);
+        Self::Passphrase(recipient)
+    }
+
+    /// Encrypt to the X25519 public keys `recipients` (`age1…`).
+    pub fn recipients(recipients: &[String]) -> Result<Self, String> {
+        if recipients.is_empty() {
+            return Err("No recipients to encrypt the results to".to_string());
+        }
+        recipients
+            .iter()
+            .map(|recipient| {
+                recipient
+                    .trim()
+                    .parse()
+                    .map_err(|e| format!("Invalid age recipient {recipient:?}: {e}"))
+            })
+            .collect::<Result<_, _>>()
+            .map(Self::Recipients)
+    }
+
+    /// Encrypt `plaintext` into a complete results file.
+    pub fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+        let encryptor = match self {
+            Self::Passphrase(recipient) => {
+                age::Encryptor::with_recipients(iter::once(recipient as &dyn age::Recipient))
+            }
+            Self::Recipients(recipients) => {
+                age::Encryptor::with_recipients(recipients.iter().map(|r| r as &dyn age::Recipient))
+            }
+        }
+        .map_err(|e| format!("Error encrypting results: {e}"))?;
+
+        let mut out = Vec::with_capacity(plaintext.len() + 256);
+        let mut writer = encryptor
+            .wrap_output(&mut out)
+            .map_err(|e| format!("Error encrypting results: {e}"))?;
+        writer
+            .write_all(plaintext)
+            .and_then(|()| writer.finish())
+            .map_err(|e| format!("Error encrypting results: {e}"))?;
+        Ok(out)
+    }
+}
+
+/// What a results file is opened with: the passphrase it was encrypted to,
+/// or an age identity file holding the secret key of one of its recipients.
+pub enum ResultsKey {
+    Passphrase(Password),
+    Identities(Vec<Box<dyn age::Identity>>),
+}
+
+impl ResultsKey {
+    /// Read the identities (`AGE-SECRET-KEY-1…` lines) of an age identity
+    /// file, as `age-keygen` writes it.
+    pub fn identity_file(path: &str) -> Result<Self, String> {
+        age::IdentityFile::from_file(path.to_string())
+            .map_err(|e| format!("Error reading identity file {path}: {e}"))?
+            .into_identities()
+            .map(Self::Identities)
+            .map_err(|e| format!("Error reading identity file {path}: {e}"))
+    }
+
+    /// Decrypt a results file written by [`ResultsCipher::seal`].
+    pub fn open(&self, data: &[u8]) -> Result<Zeroizing<Vec<u8>>, String> {
+        if !is_encrypted(data) {
+            return Err("Not an encrypted results file".to_string());
+        }
+        let decryptor = age::Decryptor::new_buffered(data).map_err(describe)?;
+        let mut reader = match self {
+            Self::Passphrase(password) => {
+                let mut identity =
+                    age::scrypt::Identity::new(SecretString::from(password.expose().to_string()));
+                identity.set_max_work_factor(MAX_SCRYPT_LOG_N);
+                decryptor.decrypt(iter::once(&identity as &dyn age::Identity))
+            }
+            Self::Identities(identities) => {
+                decryptor.decrypt(identities.iter().map(|identity| identity.as_ref()))
+            }
+        }
+        .map_err(describe)?;
+
+        let mut plaintext = Zeroizing::new(Vec::new());
+        reader
+            .read_to_end(&mut plaintext)
+            .map_err(|_| "The results file was modified or is truncated".to_string())?;
+        Ok(plaintext)
+    }
+}
+
+fn describe(e: DecryptError) -> String {
+    match e {
+        DecryptError::DecryptionFailed => {
+            "Wrong password, or the results file was modified".to_string()
+        }
+        DecryptError::NoMatchingKeys => {
+            "The results file isn't encrypted to this password or identity".to_string()
+        }
+        DecryptError::ExcessiveWork { required, .. } => {
+            format!("scrypt work factor {required} is above the maximum of {MAX_SCRYPT_LOG_N}")
+        }
+        e => format!("Malformed encrypted results file: {e}"),
+    }
+}
+
+/// Whether `data` starts like an age file, as [`ResultsCipher::seal`] writes.
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(AGE_MAGIC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_dir;
+    use age::secrecy::ExposeSecret;
+
+    fn header(sealed: &[u8]) -> String {
+        let end = sealed.windows(4).position(|w| w == b"\n---").unwrap();
+        String::from_utf8(sealed[..end].to_vec()).unwrap()
+    }
+
+    #[test]
+    fn sealed_results_open_only_with_the_passphrase() {
+        let cipher = ResultsCipher::passphrase("correct horse", 10);
+        let sealed = cipher.seal(b"{\"key_pairs\": []}").unwrap();
+        assert!(is_encrypted(&sealed));
+        assert!(header(&sealed).contains("\n-> scrypt "));
+        assert!(!sealed.windows(9).any(|w| w == b"key_pairs"));
+
+        let key = ResultsKey::Passphrase(Password::from("correct horse".to_string()));
+        assert_eq!(*key.open(&sealed).unwrap(), b"{\"key_pairs\": []}");
+        // Every save uses a fresh file key.
+        assert_ne!(header(&cipher.seal(b"{}").unwrap()), header(&sealed));
+
+        let wrong = ResultsKey::Passphrase(Password::from("wrong horse".to_string()));
+        assert!(wrong.open(&sealed).is_err());
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(key.open(&tampered).is_err());
+        assert!(key.open(b"{}").is_err());
+    }
+
+    #[test]
+    fn sealed_results_open_only_with_a_recipient_identity() {
+        let identity = age::x25519::Identity::generate();
+        let other = age::x25519::Identity::generate();
+        let cipher = ResultsCipher::recipients(&[
+            identity.to_public().to_string(),
+            other.to_public().to_string(),
+        ])
+        .unwrap();
+        let sealed = cipher.seal(b"{\"key_pairs\": []}").unwrap();
+        assert_eq!(header(&sealed).matches("\n-> X25519 ").count(), 2);
+
+        // Either recipient's identity file opens it.
+        let dir = test_dir("results_identity");
+        std::fs::create_dir_all(&dir).unwrap();
+        let identity_file = dir.join("key.txt");
+        std::fs::write(
+            &identity_file,
+            format!(
+                "# created: by a test\n{}\n",
+                other.to_string().expose_secret()
+            ),
+        )
+        .unwrap();
+        let key = ResultsKey::identity_file(&identity_file.display().to_string()).unwrap();
+        assert_eq!(*key.open(&sealed).unwrap(), b"{\"key_pairs\": []}");
+
+        let stranger = ResultsKey::Identities(vec![Box::new(age::x25519::Identity::generate())]);
+        assert!(stranger.open(&sealed).is_err());
+        let password = ResultsKey::Passphrase(Password::from("hunter2".to_string()));
+        assert!(password.open(&sealed).is_err());
+
+        assert!(ResultsCipher::recipients(&["age1nope".to_string()]).is_err());
+        assert!(ResultsCipher::recipients(&[]).is_err());
+        assert!(ResultsKey::identity_file(&dir.join("missing").display().to_string()).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
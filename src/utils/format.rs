@@ -0,0 +1,313 @@
+//! Serializers for the `--format` results formats.
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{KeyPair, KeystoreResults, PublicAddressEntry, Results};
+
+/// How results are written.
+#[derive(clap::ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputFormat {
+    /// One pretty-printed document, rewritten after every match
+    #[default]
+    Json,
+    /// One JSON record per line, appended as each key is found
+    Ndjson,
+    /// A header, then one row per key, appended as each key is found
+    Csv,
+    /// One hex private key per line, as `geth account import` reads them
+    RawKeys,
+    /// A JSON array of `{address, private_key}` (or `{address, path}` in
+    /// keystore mode), as `cast wallet new --json` prints
+    Foundry,
+}
+
+impl OutputFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            OutputFormat::Json => "json",
+            OutputFormat::Ndjson => "ndjson",
+            OutputFormat::Csv => "csv",
+            OutputFormat::RawKeys => "raw-keys",
+            OutputFormat::Foundry => "foundry",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Json | OutputFormat::Foundry => "json",
+            OutputFormat::Ndjson => "ndjson",
+            OutputFormat::Csv => "csv",
+            OutputFormat::RawKeys => "txt",
+        }
+    }
+
+    /// Whether each key is appended as a line when it is found, rather than
+    /// the whole document being rewritten.
+    pub fn is_streamed(&self) -> bool {
+        matches!(
+            self,
+            OutputFormat::Ndjson | OutputFormat::Csv | OutputFormat::RawKeys
+        )
+    }
+
+    /// Whether the format can hold keystore-mode results, which have no
+    /// plaintext keys.
+    pub fn supports_keystore(&self) -> bool {
+        *self != OutputFormat::RawKeys
+    }
+}
+
+/// One found key, as [`KeyPair`] or, in keystore mode, [`PublicAddressEntry`].
+#[derive(Serialize, Debug, Clone, Copy)]
+#[serde(untagged)]
+pub enum ResultRecord<'a> {
+    KeyPair(&'a KeyPair),
+    Keystore(&'a PublicAddressEntry),
+}
+
+/// The first line of a streamed format, before any record, if it has one.
+pub fn header(format: OutputFormat, keystore: bool) -> Option<&'static str> {
+    match format {
+        OutputFormat::Csv if keystore => {
+            Some("public_address,keystore_file,contract_address,word,derivation_path")
+        }
+        OutputFormat::Csv => {
+            Some("private_key,public_address,contract_address,word,mnemonic,derivation_path")
+        }
+        _ => None,
+    }
+}
+
+/// One record as a line of a streamed format, without the newline.
+pub fn record_line(format: OutputFormat, record: ResultRecord<'_>) -> Result<String, String> {
+    match (format, record) {
+        (OutputFormat::Ndjson, record) => {
+            serde_json::to_string(&record).map_err(|e| format!("Error serializing result: {e}"))
+        }
+        (OutputFormat::Csv, ResultRecord::KeyPair(entry)) => Ok(csv_row(&[
+            Some(entry.private_key.expose()),
+            Some(&entry.public_address),
+            entry.contract_address.as_deref(),
+            entry.word.as_deref(),
+            entry.mnemonic.as_deref(),
+            entry.derivation_path.as_deref(),
+        ])),
+        (OutputFormat::Csv, ResultRecord::Keystore(entry)) => Ok(csv_row(&[
+            Some(&entry.public_address),
+            Some(&entry.keystore_file),
+            entry.contract_address.as_deref(),
+            entry.word.as_deref(),
+            entry.derivation_path.as_deref(),
+        ])),
+        (OutputFormat::RawKeys, ResultRecord::KeyPair(entry)) => {
+            Ok(entry.private_key.expose().to_string())
+        }
+        (OutputFormat::RawKeys, ResultRecord::Keystore(_)) => Err(keystore_unsupported(format)),
+        (OutputFormat::Json | OutputFormat::Foundry, _) => Err(format!(
+            "{} results are a single document, not one line per key",
+            format.name()
+        )),
+    }
+}
+
+/// The whole results document in `format`: the keystore results if there
+/// are any, otherwise the plain ones.
+pub fn serialize_results(
+    format: OutputFormat,
+    results: &Option<Results>,
+    keystore_results: &Option<KeystoreResults>,
+) -> Result<String, String> {
+    let records: Vec<ResultRecord> = match (results, keystore_results) {
+        (_, Some(keystore_results)) => keystore_results
+            .addresses
+            .iter()
+            .map(ResultRecord::Keystore)
+            .collect(),
+        (Some(results), None) => results
+            .key_pairs
+            .iter()
+            .map(ResultRecord::KeyPair)
+            .collect(),
+        (None, None) => return Err("No results to save".to_string()),
+    };
+    let keystore = keystore_results.is_some();
+
+    match format {
+        OutputFormat::Json => {
+            let json = match keystore_results {
+                Some(keystore_results) => serde_json::to_string_pretty(keystore_results),
+                None => serde_json::to_string_pretty(results),
+            };
+            json.map_err(|e| format!("Error serializing results: {e}"))
+        }
+        OutputFormat::Foundry => {
+            let wallets: Vec<serde_json::Value> = records
+                .iter()
+                .map(|record| match record {
+                    ResultRecord::KeyPair(entry) => serde_json::json!({
+                        "address": entry.public_address,
+                        "private_key": format!("0x{}", entry.private_key.expose()),
+                    }),
+                    ResultRecord::Keystore(entry) => serde_json::json!({
+                        "address": entry.public_address,
+                        "path": entry.keystore_file,
+                    }),
+                })
+                .collect();
+            serde_json::to_string_pretty(&wallets)
+                .map_err(|e| format!("Error serializing results: {e}"))
+        }
+        OutputFormat::Ndjson | OutputFormat::Csv | OutputFormat::RawKeys => {
+            if keystore && !format.supports_keystore() {
+                return Err(keystore_unsupported(format));
+            }
+            let mut out = String::new();
+            if let Some(header) = header(format, keystore) {
+                out.push_str(header);
+                out.push('\n');
+            }
+            for record in records {
+                out.push_str(&record_line(format, record)?);
+                out.push('\n');
+            }
+            Ok(out)
+        }
+    }
+}
+
+fn keystore_unsupported(format: OutputFormat) -> String {
+    format!(
+        "{} output needs plaintext keys and can't be used with --keystore",
+        format.name()
+    )
+}
+
+/// A CSV row, quoting fields that contain a comma, quote or line break.
+fn csv_row(fields: &[Option<&str>]) -> String {
+    fields
+        .iter()
+        .map(|field| {
+            let field = field.unwrap_or("");
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn results() -> Results {
+        Results {
+            timestamp: "2024-01-01T00:00:00+00:00".to_string(),
+            rule: "prefix_ab".to_string(),
+            contract_nonce: None,
+            key_pairs: vec![
+                KeyPair {
+                    private_key: "11".repeat(32).into(),
+                    public_address: "0xab00000000000000000000000000000000000001".to_string(),
+                    contract_address: None,
+                    word: None,
+                    mnemonic: None,
+                    derivation_path: None,
+                },
+                KeyPair {
+                    private_key: "22".repeat(32).into(),
+                    public_address: "0xab00000000000000000000000000000000000002".to_string(),
+                    contract_address: None,
+                    word: Some("ab, \"quoted\"".to_string()),
+                    mnemonic: Some("test test junk".to_string()),
+                    derivation_path: Some("m/44'/60'/0'/0/0".to_string()),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn csv_and_raw_keys_have_one_line_per_key() {
+        let results = Some(results());
+        let csv = serialize_results(OutputFormat::Csv, &results, &None).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], header(OutputFormat::Csv, false).unwrap());
+        assert_eq!(
+            lines[1],
+            format!(
+                "{},0xab00000000000000000000000000000000000001,,,,",
+                "11".repeat(32)
+            )
+        );
+        assert!(lines[2].ends_with(",\"ab, \"\"quoted\"\"\",test test junk,m/44'/60'/0'/0/0"));
+
+        let raw = serialize_results(OutputFormat::RawKeys, &results, &None).unwrap();
+        assert_eq!(raw, format!("{}\n{}\n", "11".repeat(32), "22".repeat(32)));
+    }
+
+    #[test]
+    fn streamed_lines_add_up_to_the_document() {
+        let results = results();
+        for format in [
+            OutputFormat::Ndjson,
+            OutputFormat::Csv,
+            OutputFormat::RawKeys,
+        ] {
+            let mut streamed: String = header(format, false)
+                .map(|h| format!("{h}\n"))
+                .unwrap_or_default();
+            for entry in &results.key_pairs {
+                streamed.push_str(&record_line(format, ResultRecord::KeyPair(entry)).unwrap());
+                streamed.push('\n');
+            }
+            assert_eq!(
+                streamed,
+                serialize_results(format, &Some(results.clone()), &None).unwrap(),
+                "{format:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn foundry_lists_wallets_like_cast() {
+        let json = serialize_results(OutputFormat::Foundry, &Some(results()), &None).unwrap();
+        let wallets: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            wallets[0],
+            serde_json::json!({
+                "address": "0xab00000000000000000000000000000000000001",
+                "private_key": format!("0x{}", "11".repeat(32)),
+            })
+        );
+        assert_eq!(wallets.as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn keystore_results_never_yield_raw_keys() {
+        let keystore_results = Some(KeystoreResults {
+            timestamp: "2024-01-01T00:00:00+00:00".to_string(),
+            rule: "prefix_ab".to_string(),
+            contract_nonce: None,
+            encryption: None,
+            addresses: vec![PublicAddressEntry {
+                public_address: "0xab00000000000000000000000000000000000001".to_string(),
+                keystore_file: "results/keystore/UTC--x--ab01".to_string(),
+                contract_address: None,
+                word: None,
+                derivation_path: None,
+            }],
+        });
+        assert!(serialize_results(OutputFormat::RawKeys, &None, &keystore_results).is_err());
+        let csv = serialize_results(OutputFormat::Csv, &None, &keystore_results).unwrap();
+        assert_eq!(
+            csv.lines().nth(1).unwrap(),
+            "0xab00000000000000000000000000000000000001,results/keystore/UTC--x--ab01,,,"
+        );
+        let foundry = serialize_results(OutputFormat::Foundry, &None, &keystore_results).unwrap();
+        assert!(foundry.contains("\"path\": \"results/keystore/UTC--x--ab01\""));
+    }
+}
@@ -1,55 +1,352 @@
 use chrono::Local;
-use std::fs::File;
-use std::io::Write;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use zeroize::Zeroizing;
 
-use crate::models::{KeystoreResults, Results};
+use crate::models::{Checkpoint, Create2Results, KeystoreResults, Results, SplitResults};
 
-/// Create output directory if it doesn't exist
+mod encrypt;
+mod format;
+mod private_file;
+mod sink;
+
+pub use encrypt::{is_encrypted, ResultsCipher, ResultsKey, RESULTS_SCRYPT_LOG_N};
+pub use format::{header, record_line, serialize_results, OutputFormat, ResultRecord};
+pub use private_file::{
+    append_private_file, create_private_dir, create_private_file, write_private_file,
+};
+pub use sink::{open_sink, DocumentFile, RecordWriter, ResultSink, STDOUT};
+
+static MESSAGES_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Print human-readable messages ([`say!`](crate::say) and
+/// [`say_partial!`](crate::say_partial)) to stderr from now on, leaving
+/// stdout to results piped into another program.
+pub fn messages_to_stderr() {
+    MESSAGES_TO_STDERR.store(true, Ordering::Relaxed);
+}
+
+/// Whether [`messages_to_stderr`] was called.
+pub fn messages_on_stderr() -> bool {
+    MESSAGES_TO_STDERR.load(Ordering::Relaxed)
+}
+
+/// `println!` for human-readable messages, which go to stderr once
+/// [`messages_to_stderr`] was called.
+#[macro_export]
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if $crate::utils::messages_on_stderr() {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// `print!` and flush, for progress lines redrawn with `\r`, on the same
+/// stream as [`say!`].
+#[macro_export]
+macro_rules! say_partial {
+    ($($arg:tt)*) => {{
+        use std::io::Write as _;
+        if $crate::utils::messages_on_stderr() {
+            eprint!($($arg)*);
+            let _ = std::io::stderr().flush();
+        } else {
+            print!($($arg)*);
+            let _ = std::io::stdout().flush();
+        }
+    }};
+}
+
+/// Create output directory if it doesn't exist, readable by its owner only
 pub fn ensure_output_dir(dir: &str) -> std::io::Result<()> {
     if !Path::new(dir).exists() {
-        std::fs::create_dir_all(dir)?;
+        create_private_dir(Path::new(dir))?;
     }
     Ok(())
 }
 
-/// Generate a filename with timestamp and rule
-pub fn generate_filename(dir: &str, rule: &str) -> String {
+/// Generate a filename with timestamp, rule and extension
+pub fn generate_filename(dir: &str, rule: &str, extension: &str) -> String {
     let timestamp = Local::now().format("%Y%m%d_%H%M%S").to_string();
-    format!("{dir}/eth_addresses_{timestamp}_{rule}.json")
+    format!("{dir}/eth_addresses_{timestamp}_{rule}.{extension}")
+}
+
+/// Checkpoint filename for a results file: `foo.json`, `foo.ndjson` or the
+/// encrypted `foo.json.age` → `foo.checkpoint.json`
+pub fn checkpoint_filename(results_filename: &str) -> String {
+    let results_filename = results_filename
+        .strip_suffix(".age")
+        .unwrap_or(results_filename);
+    let stem = Path::new(results_filename).with_extension("");
+    format!("{}.checkpoint.json", stem.display())
 }
 
-/// Save results to a JSON file
+/// Save a search checkpoint, replacing any previous one.
+///
+/// Written with [`write_private_file`], so a crash mid-write leaves the
+/// previous checkpoint intact rather than a truncated one.
+pub fn save_checkpoint(filename: &str, checkpoint: &Checkpoint) -> Result<(), String> {
+    if checkpoint.keystore_dir.is_some() && checkpoint.results.is_some() {
+        return Err("Refusing to write plaintext keys to a keystore-mode checkpoint".to_string());
+    }
+    if checkpoint.encrypted
+        && (checkpoint.results.is_some() || checkpoint.keystore_results.is_some())
+    {
+        return Err(
+            "Refusing to write results to the checkpoint of an encrypted search".to_string(),
+        );
+    }
+    let json = serde_json::to_string_pretty(checkpoint)
+        .map(Zeroizing::new)
+        .map_err(|e| format!("Error serializing checkpoint: {e}"))?;
+    write_private_file(Path::new(filename), json.as_bytes(), true)
+        .map_err(|e| format!("Error writing checkpoint: {e}"))
+}
+
+/// Load a checkpoint written by [`save_checkpoint`]
+pub fn load_checkpoint(filename: &str) -> Result<Checkpoint, String> {
+    let contents = std::fs::read_to_string(filename)
+        .map_err(|e| format!("Error reading checkpoint {filename}: {e}"))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Error parsing checkpoint {filename}: {e}"))
+}
+
+/// Save the results document to a file in `format`, encrypted with
+/// `cipher` if given. The file is written with [`write_private_file`], and
+/// an existing one is only replaced with `replace`.
 pub fn save_results(
     filename: &str,
+    format: OutputFormat,
     results: &Option<Results>,
     keystore_results: &Option<KeystoreResults>,
+    cipher: Option<&ResultsCipher>,
+    replace: bool,
 ) -> Result<(), String> {
-    match File::create(filename) {
-        Ok(mut file) => {
-            // Serialize the appropriate results structure
-            let json = if let Some(keystore_results) = keystore_results {
-                match serde_json::to_string_pretty(keystore_results) {
-                    Ok(json) => json,
-                    Err(e) => return Err(format!("Error serializing keystore results: {e}")),
-                }
-            } else if let Some(results) = results {
-                match serde_json::to_string_pretty(results) {
-                    Ok(json) => json,
-                    Err(e) => return Err(format!("Error serializing results: {e}")),
-                }
-            } else {
-                return Err("No results to save".to_string());
-            };
-
-            // Write the JSON to the file
-            if let Err(e) = file.write_all(json.as_bytes()) {
-                return Err(format!("Error writing to file: {e}"));
-            }
-
-            println!("Results saved to {filename}");
-            Ok(())
+    let contents = Zeroizing::new(serialize_results(format, results, keystore_results)?);
+    let contents = match cipher {
+        Some(cipher) => cipher.seal(contents.as_bytes())?,
+        None => contents.as_bytes().to_vec(),
+    };
+    let contents = Zeroizing::new(contents);
+    write_private_file(Path::new(filename), &contents, replace)
+        .map_err(|e| format!("Error writing {filename}: {e}"))?;
+    say!("Results saved to {filename}");
+    Ok(())
+}
+
+/// Read and decrypt a results file saved with a [`ResultsCipher`], returning
+/// the plaintext document
+pub fn decrypt_results_file(filename: &str, key: &ResultsKey) -> Result<Zeroizing<String>, String> {
+    let data = std::fs::read(filename).map_err(|e| format!("Error reading {filename}: {e}"))?;
+    let plaintext = key.open(&data).map_err(|e| format!("{filename}: {e}"))?;
+    std::str::from_utf8(&plaintext)
+        .map(|document| Zeroizing::new(document.to_string()))
+        .map_err(|_| format!("{filename}: decrypted results are not UTF-8"))
+}
+
+/// Save CREATE2 salt mining results to a JSON file, with
+/// [`write_private_file`]; an existing file is only replaced with `replace`.
+pub fn save_create2_results(
+    filename: &str,
+    results: &Create2Results,
+    replace: bool,
+) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(results)
+        .map_err(|e| format!("Error serializing CREATE2 results: {e}"))?;
+    write_private_file(Path::new(filename), json.as_bytes(), replace)
+        .map_err(|e| format!("Error writing {filename}: {e}"))?;
+    say!("Results saved to {filename}");
+    Ok(())
+}
+
+/// Save the partial keys of a split-key search to a JSON file, with
+/// [`write_private_file`]; an existing file is only replaced with `replace`.
+pub fn save_split_results(
+    filename: &str,
+    results: &SplitResults,
+    replace: bool,
+) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(results)
+        .map(Zeroizing::new)
+        .map_err(|e| format!("Error serializing split results: {e}"))?;
+    write_private_file(Path::new(filename), json.as_bytes(), replace)
+        .map_err(|e| format!("Error writing {filename}: {e}"))?;
+    say!("Results saved to {filename}");
+    Ok(())
+}
+
+/// Load a results file written by [`save_split_results`]
+pub fn load_split_results(filename: &str) -> Result<SplitResults, String> {
+    let contents = std::fs::read_to_string(filename)
+        .map_err(|e| format!("Error reading split results {filename}: {e}"))?;
+    serde_json::from_str(&contents)
+        .map_err(|e| format!("Error parsing split results {filename}: {e}"))
+}
+
+/// A path under the system temp directory for one test's files, unique to
+/// this process and call so that leftovers from earlier runs, or tests
+/// running in parallel, never get in the way. The directory isn't created.
+#[cfg(test)]
+pub(crate) fn test_dir(name: &str) -> std::path::PathBuf {
+    use rand::RngCore;
+
+    let mut suffix = [0u8; 8];
+    rand::thread_rng().fill_bytes(&mut suffix);
+    std::env::temp_dir().join(format!(
+        "address_finder_{name}_{}_{}",
+        std::process::id(),
+        hex::encode(suffix)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keystore::KeystoreOptions;
+    use crate::models::{KeyPair, PublicAddressEntry};
+
+    fn checkpoint() -> Checkpoint {
+        Checkpoint {
+            timestamp: "2024-01-01T00:00:00+00:00".to_string(),
+            rule: "prefix_abc".to_string(),
+            prefix: Some(vec!["abc".to_string()]),
+            suffix: None,
+            pattern: None,
+            dictionary: None,
+            case_sensitive: false,
+            contract_nonce: None,
+            seed: None,
+            count: 3,
+            checked: 123_456,
+            elapsed_secs: 42.5,
+            completed: false,
+            results_file: "results/eth_addresses_x_prefix_abc.json".to_string(),
+            format: OutputFormat::Json,
+            encrypted: false,
+            encrypted_to: Vec::new(),
+            keystore_dir: None,
+            results: Some(Results {
+                timestamp: "2024-01-01T00:00:00+00:00".to_string(),
+                rule: "prefix_abc".to_string(),
+                contract_nonce: None,
+                key_pairs: vec![KeyPair {
+                    private_key: "11".repeat(32).into(),
+                    public_address: "0xabc0000000000000000000000000000000000000".to_string(),
+                    contract_address: None,
+                    word: None,
+                    mnemonic: None,
+                    derivation_path: None,
+                }],
+            }),
+            keystore_results: None,
         }
-        Err(e) => Err(format!("Error creating file: {e}")),
+    }
+
+    #[test]
+    fn checkpoint_filename_sits_next_to_results() {
+        assert_eq!(
+            checkpoint_filename("results/eth_addresses_1_prefix_ab.json"),
+            "results/eth_addresses_1_prefix_ab.checkpoint.json"
+        );
+        assert_eq!(
+            checkpoint_filename("results/eth_addresses_1_prefix_ab.ndjson"),
+            "results/eth_addresses_1_prefix_ab.checkpoint.json"
+        );
+        assert_eq!(
+            checkpoint_filename("results/eth_addresses_1_prefix_ab.json.age"),
+            "results/eth_addresses_1_prefix_ab.checkpoint.json"
+        );
+    }
+
+    #[test]
+    fn checkpoint_round_trips() {
+        let dir = test_dir("checkpoint_round_trip");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("job.checkpoint.json").display().to_string();
+
+        save_checkpoint(&path, &checkpoint()).unwrap();
+        let loaded = load_checkpoint(&path).unwrap();
+        assert_eq!(loaded.checked, 123_456);
+        assert_eq!(loaded.count, 3);
+        assert_eq!(loaded.results.unwrap().key_pairs.len(), 1);
+        assert!(!Path::new(&format!("{path}.tmp")).exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn keystore_checkpoint_refuses_plaintext_keys() {
+        let dir = test_dir("checkpoint_keystore_plaintext");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("job.checkpoint.json").display().to_string();
+
+        let mut cp = checkpoint();
+        cp.keystore_dir = Some("results/keystore".to_string());
+        assert!(save_checkpoint(&path, &cp).is_err());
+        assert!(!Path::new(&path).exists());
+
+        cp.results = None;
+        cp.keystore_results = Some(KeystoreResults {
+            timestamp: cp.timestamp.clone(),
+            rule: cp.rule.clone(),
+            contract_nonce: None,
+            encryption: Some(KeystoreOptions::default()),
+            addresses: vec![PublicAddressEntry {
+                public_address: "0xabc0000000000000000000000000000000000000".to_string(),
+                keystore_file: "results/keystore/UTC--x--abc".to_string(),
+                contract_address: None,
+                word: None,
+                derivation_path: None,
+            }],
+        });
+        save_checkpoint(&path, &cp).unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains("private_key"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn encrypted_results_stay_out_of_the_file_and_checkpoint() {
+        let dir = test_dir("encrypted_results");
+        std::fs::create_dir_all(&dir).unwrap();
+        let results_path = dir.join("job.json.age").display().to_string();
+        let checkpoint_path = dir.join("job.checkpoint.json").display().to_string();
+
+        let mut cp = checkpoint();
+        let identity = age::x25519::Identity::generate();
+        cp.encrypted_to = vec![identity.to_public().to_string()];
+        let cipher = ResultsCipher::recipients(&cp.encrypted_to).unwrap();
+        save_results(
+            &results_path,
+            OutputFormat::Json,
+            &cp.results,
+            &None,
+            Some(&cipher),
+            false,
+        )
+        .unwrap();
+        let on_disk = std::fs::read(&results_path).unwrap();
+        assert!(is_encrypted(&on_disk));
+        assert!(!String::from_utf8_lossy(&on_disk).contains(&"11".repeat(32)));
+
+        let key = ResultsKey::Identities(vec![Box::new(identity)]);
+        let document = decrypt_results_file(&results_path, &key).unwrap();
+        let results: Results = serde_json::from_str(&document).unwrap();
+        assert_eq!(results.key_pairs[0].private_key.expose(), "11".repeat(32));
+        let stranger = ResultsKey::Identities(vec![Box::new(age::x25519::Identity::generate())]);
+        assert!(decrypt_results_file(&results_path, &stranger).is_err());
+
+        cp.encrypted = true;
+        assert!(save_checkpoint(&checkpoint_path, &cp).is_err());
+        cp.results = None;
+        save_checkpoint(&checkpoint_path, &cp).unwrap();
+        let loaded = load_checkpoint(&checkpoint_path).unwrap();
+        assert!(loaded.encrypted);
+        assert_eq!(loaded.encrypted_to, cp.encrypted_to);
+
+        let _ = std::fs::remove_dir_all(&dir);
     }
 }
@@ -0,0 +1,203 @@
+//! Writes for files that hold keys or key material.
+//!
+//! Files are created readable by their owner only (0600) and directories
+//! 0700 on Unix, whatever the umask. Whole documents go to a temporary file
+//! in the same directory, are synced, and are then moved into place in one
+//! step, so a reader or a crash never sees a partly written file.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use rand::RngCore;
+
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+
+/// Create `dir` and any missing parents, owner-only. Directories that
+/// already exist are left as they are.
+pub fn create_private_dir(dir: &Path) -> io::Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    builder.mode(0o700);
+    builder.create(dir)
+}
+
+/// Options for a new owner-only file; `create_new` refuses to open a file
+/// that already exists.
+fn private_options(create_new: bool) -> OpenOptions {
+    let mut options = OpenOptions::new();
+    options.write(true);
+    if create_new {
+        options.create_new(true);
+    } else {
+        options.create(true);
+    }
+    #[cfg(unix)]
+    options.mode(0o600);
+    options
+}
+
+/// Create `path` for writing, owner-only, failing if it already exists.
+pub fn create_private_file(path: &Path) -> io::Result<File> {
+    private_options(true).open(path).map_err(already_exists)
+}
+
+/// Open `path` to append to it, creating it owner-only if it is missing.
+pub fn append_private_file(path: &Path) -> io::Result<File> {
+    private_options(false).append(true).open(path)
+}
+
+/// Atomically write `contents` to `path`, owner-only. With `replace` an
+/// existing file is swapped for the new one; without it the write fails if
+/// `path` exists, even if it appears while the contents are being written.
+pub fn write_private_file(path: &Path, contents: &[u8], replace: bool) -> io::Result<()> {
+    if !replace && path.exists() {
+        return Err(already_exists(io::ErrorKind::AlreadyExists.into()));
+    }
+    let tmp = temp_path(path);
+    let result = write_synced(&tmp, contents).and_then(|()| {
+        if replace {
+            fs::rename(&tmp, path)
+        } else {
+            // A hard link, unlike a rename, never replaces its target. On
+            // filesystems without hard links, fall back to a checked rename.
+            match fs::hard_link(&tmp, path) {
+                Err(e) if e.kind() != io::ErrorKind::AlreadyExists && !path.exists() => {
+                    fs::rename(&tmp, path)
+                }
+                result => result.map_err(already_exists),
+            }
+        }
+    });
+    // After a rename this is a no-op; otherwise it drops the temporary file.
+    let _ = fs::remove_file(&tmp);
+    result?;
+    sync_parent(path)
+}
+
+fn write_synced(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut file = private_options(true).open(path)?;
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+/// `.<name>.<random>.tmp` next to `path`, so the final rename never
+/// crosses filesystems.
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut suffix = [0u8; 8];
+    rand::thread_rng().fill_bytes(&mut suffix);
+    path.with_file_name(format!(".{name}.{}.tmp", hex::encode(suffix)))
+}
+
+/// Persist the rename itself. Directories can't be opened for syncing on
+/// Windows, where this is skipped.
+fn sync_parent(path: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        File::open(parent)?.sync_all()?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+fn already_exists(e: io::Error) -> io::Error {
+    if e.kind() == io::ErrorKind::AlreadyExists {
+        io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "file already exists; refusing to overwrite it",
+        )
+    } else {
+        e
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_dir;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    #[cfg(unix)]
+    fn mode(path: &Path) -> u32 {
+        use std::os::unix::fs::PermissionsExt;
+        fs::metadata(path).unwrap().permissions().mode() & 0o777
+    }
+
+    #[test]
+    fn secret_files_are_owner_only_and_never_clobbered() {
+        let dir = test_dir("private_file_modes").join("nested");
+        create_private_dir(&dir).unwrap();
+        let path = dir.join("keys.json");
+
+        write_private_file(&path, b"first", false).unwrap();
+        let err = write_private_file(&path, b"second", false).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert!(create_private_file(&path).is_err());
+        assert_eq!(fs::read(&path).unwrap(), b"first");
+
+        write_private_file(&path, b"second", true).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"second");
+        writeln!(append_private_file(&dir.join("keys.csv")).unwrap(), "a").unwrap();
+
+        #[cfg(unix)]
+        {
+            assert_eq!(mode(&dir), 0o700);
+            assert_eq!(mode(&path), 0o600);
+            assert_eq!(mode(&dir.join("keys.csv")), 0o600);
+        }
+        // No temporary files are left behind.
+        let mut names: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["keys.csv", "keys.json"]);
+
+        let _ = fs::remove_dir_all(dir.parent().unwrap());
+    }
+
+    // Windows refuses to replace a file another handle has open.
+    #[cfg(unix)]
+    #[test]
+    fn readers_never_see_a_partial_file() {
+        let dir = test_dir("private_file_atomic");
+        create_private_dir(&dir).unwrap();
+        let path = dir.join("results.json");
+        const LEN: usize = 1 << 20;
+        write_private_file(&path, &vec![b'a'; LEN], false).unwrap();
+
+        let done = Arc::new(AtomicBool::new(false));
+        let reader = {
+            let (path, done) = (path.clone(), Arc::clone(&done));
+            std::thread::spawn(move || {
+                let mut reads = 0;
+                while !done.load(Ordering::Relaxed) {
+                    let contents = fs::read(&path).unwrap();
+                    assert_eq!(contents.len(), LEN);
+                    assert!(contents.iter().all(|&b| b == contents[0]));
+                    reads += 1;
+                }
+                reads
+            })
+        };
+        for byte in b"bcdefghijk".iter().cycle().take(30) {
+            write_private_file(&path, &vec![*byte; LEN], true).unwrap();
+        }
+        done.store(true, Ordering::Relaxed);
+        assert!(reader.join().unwrap() > 0);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,250 @@
+//! Destinations for the keys a search finds.
+
+use std::io::{self, Write};
+use std::path::Path;
+
+use zeroize::Zeroizing;
+
+use super::format::{header, record_line, OutputFormat, ResultRecord};
+use super::{append_private_file, create_private_file, save_results, ResultsCipher};
+use crate::models::{KeystoreResults, Results};
+
+/// `--output` value that sends results to stdout.
+pub const STDOUT: &str = "-";
+
+/// Where a search's results go. The caller keeps the results document; the
+/// sink is handed each new record as it is found and the whole document
+/// whenever it changes, and writes whichever its format needs.
+pub trait ResultSink: Send {
+    fn record(&mut self, record: ResultRecord<'_>) -> Result<(), String>;
+
+    fn save(
+        &mut self,
+        results: &Option<Results>,
+        keystore_results: &Option<KeystoreResults>,
+    ) -> Result<(), String>;
+
+    /// The results file, or `stdout`, for messages.
+    fn location(&self) -> &str;
+}
+
+/// Rewrites the whole results document with [`save_results`] on every save,
+/// for formats that are not streamed, encrypting it if given a cipher. The
+/// first save of a new search refuses to replace an existing file.
+pub struct DocumentFile {
+    filename: String,
+    format: OutputFormat,
+    cipher: Option<ResultsCipher>,
+    replace: bool,
+}
+
+impl ResultSink for DocumentFile {
+    fn record(&mut self, _record: ResultRecord<'_>) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn save(
+        &mut self,
+        results: &Option<Results>,
+        keystore_results: &Option<KeystoreResults>,
+    ) -> Result<(), String> {
+        save_results(
+            &self.filename,
+            self.format,
+            results,
+            keystore_results,
+            self.cipher.as_ref(),
+            self.replace,
+        )?;
+        self.replace = true;
+        Ok(())
+    }
+
+    fn location(&self) -> &str {
+        &self.filename
+    }
+}
+
+/// Writes each record as one line of a streamed format and flushes it
+/// straight away, so a consumer at the other end of a pipe sees every match
+/// as it happens. The format's header, if any, goes before the first record.
+pub struct RecordWriter<W> {
+    writer: W,
+    format: OutputFormat,
+    location: String,
+    header_written: bool,
+}
+
+impl<W: Write + Send> RecordWriter<W> {
+    /// `header_written` is true when appending to output that already
+    /// starts with the header.
+    pub fn new(writer: W, format: OutputFormat, location: &str, header_written: bool) -> Self {
+        Self {
+            writer,
+            format,
+            location: location.to_string(),
+            header_written,
+        }
+    }
+}
+
+impl<W: Write + Send> ResultSink for RecordWriter<W> {
+    fn record(&mut self, record: ResultRecord<'_>) -> Result<(), String> {
+        let line = Zeroizing::new(record_line(self.format, record)?);
+        let keystore = matches!(record, ResultRecord::Keystore(_));
+        let header = match header(self.format, keystore) {
+            Some(header) if !self.header_written => format!("{header}\n"),
+            _ => String::new(),
+        };
+        writeln!(self.writer, "{header}{}", *line)
+            .and_then(|()| self.writer.flush())
+            .map_err(|e| format!("Error writing to {}: {e}", self.location))?;
+        self.header_written = true;
+        Ok(())
+    }
+
+    fn save(
+        &mut self,
+        _results: &Option<Results>,
+        _keystore_results: &Option<KeystoreResults>,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn location(&self) -> &str {
+        &self.location
+    }
+}
+
+/// Open the sink for `destination`, a file or [`STDOUT`]. A new search never
+/// overwrites an existing file; a resumed one appends to its streamed file
+/// or replaces its document. Only whole documents can be encrypted with
+/// `cipher`.
+pub fn open_sink(
+    destination: &str,
+    format: OutputFormat,
+    resume: bool,
+    cipher: Option<ResultsCipher>,
+) -> Result<Box<dyn ResultSink>, String> {
+    if cipher.is_some() && format.is_streamed() {
+        return Err(format!(
+            "{} results are written a line at a time and can't be encrypted; use --format json",
+            format.name()
+        ));
+    }
+    if !format.is_streamed() {
+        if destination == STDOUT {
+            return Err(format!(
+                "{} results are rewritten after every match and can't go to stdout; use --format ndjson, csv or raw-keys",
+                format.name()
+            ));
+        }
+        return Ok(Box::new(DocumentFile {
+            filename: destination.to_string(),
+            format,
+            cipher,
+            replace: resume,
+        }));
+    }
+    if destination == STDOUT {
+        return Ok(Box::new(RecordWriter::new(
+            io::stdout(),
+            format,
+            "stdout",
+            false,
+        )));
+    }
+    let path = Path::new(destination);
+    let file = if resume {
+        append_private_file(path)
+    } else {
+        create_private_file(path)
+    }
+    .map_err(|e| format!("Error creating file {destination}: {e}"))?;
+    let header_written = file.metadata().map(|m| m.len() > 0).unwrap_or(false);
+    Ok(Box::new(RecordWriter::new(
+        file,
+        format,
+        destination,
+        header_written,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{KeyPair, PublicAddressEntry};
+    use crate::utils::test_dir;
+
+    fn key_pair(address: &str) -> KeyPair {
+        KeyPair {
+            private_key: "11".repeat(32).into(),
+            public_address: address.to_string(),
+            contract_address: None,
+            word: None,
+            mnemonic: None,
+            derivation_path: None,
+        }
+    }
+
+    #[test]
+    fn ndjson_writes_one_record_per_line() {
+        let mut sink = RecordWriter::new(Vec::new(), OutputFormat::Ndjson, "memory", false);
+        sink.record(ResultRecord::KeyPair(&key_pair("0xabc")))
+            .unwrap();
+        sink.record(ResultRecord::Keystore(&PublicAddressEntry {
+            public_address: "0xdef".to_string(),
+            keystore_file: "keystore/UTC--x--def".to_string(),
+            contract_address: None,
+            word: Some("deaf".to_string()),
+            derivation_path: None,
+        }))
+        .unwrap();
+        sink.save(&None, &None).unwrap();
+
+        let text = String::from_utf8(sink.writer).unwrap();
+        let lines: Vec<serde_json::Value> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["public_address"], "0xabc");
+        assert_eq!(lines[0]["private_key"], "11".repeat(32));
+        assert_eq!(lines[1]["keystore_file"], "keystore/UTC--x--def");
+        assert_eq!(lines[1]["word"], "deaf");
+    }
+
+    #[test]
+    fn streamed_files_are_appended_to_on_resume() {
+        let dir = test_dir("sink_test_resume");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("results.csv").display().to_string();
+
+        for (address, resume) in [("0x1", false), ("0x2", true)] {
+            let mut sink = open_sink(&path, OutputFormat::Csv, resume, None).unwrap();
+            sink.record(ResultRecord::KeyPair(&key_pair(address)))
+                .unwrap();
+        }
+        let csv = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(csv.lines().count(), 3, "one header and two rows: {csv}");
+
+        // A fresh search never overwrites an existing file.
+        assert!(open_sink(&path, OutputFormat::Csv, false, None).is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), csv);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn documents_cannot_stream_to_stdout() {
+        for format in [OutputFormat::Json, OutputFormat::Foundry] {
+            assert!(open_sink(STDOUT, format, false, None).is_err());
+        }
+        assert_eq!(
+            open_sink(STDOUT, OutputFormat::Ndjson, false, None)
+                .unwrap()
+                .location(),
+            "stdout"
+        );
+    }
+}